@@ -0,0 +1,428 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, BufRead},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::{home_dir, Browser, BrowserType},
+    supported_browsers::known_browsers,
+};
+
+const FIREFOX_HINTS: [&str; 8] = [
+    "firefox",
+    "librewolf",
+    "waterfox",
+    "floorp",
+    "zen-browser",
+    "mullvad",
+    "icecat",
+    "iceweasel",
+];
+
+const CHROMIUM_HINTS: [&str; 11] = [
+    "chrom", "brave", "vivaldi", "edge", "opera", "yandex", "whale", "thorium", "slimjet",
+    "cromite", "iridium",
+];
+
+const FALKON_HINTS: [&str; 1] = ["falkon"];
+
+const EPIPHANY_HINTS: [&str; 2] = ["epiphany", "gnome web"];
+
+const QUTEBROWSER_HINTS: [&str; 1] = ["qutebrowser"];
+
+/// Guesses the engine of an unknown browser from its executable or desktop file name.
+pub fn classify(hint: &str) -> Option<BrowserType> {
+    let hint = hint.to_lowercase();
+
+    if FALKON_HINTS.iter().any(|h| hint.contains(h)) {
+        Some(BrowserType::Falkon)
+    } else if EPIPHANY_HINTS.iter().any(|h| hint.contains(h)) {
+        Some(BrowserType::Epiphany)
+    } else if QUTEBROWSER_HINTS.iter().any(|h| hint.contains(h)) {
+        Some(BrowserType::Qutebrowser)
+    } else if FIREFOX_HINTS.iter().any(|h| hint.contains(h)) {
+        Some(BrowserType::Firefox)
+    } else if CHROMIUM_HINTS.iter().any(|h| hint.contains(h)) {
+        Some(BrowserType::Chromium)
+    } else {
+        None
+    }
+}
+
+fn sandboxed() -> bool {
+    std::env::var("FLATPAK_ID").is_ok()
+}
+
+/// Inside flatpak the host system is only reachable below `/run/host`.
+pub fn host_path(path: &Path) -> PathBuf {
+    if sandboxed() {
+        if let Ok(relative) = path.strip_prefix("/") {
+            let on_host = Path::new("/run/host").join(relative);
+
+            if on_host.exists() {
+                return on_host;
+            }
+        }
+    }
+
+    path.to_path_buf()
+}
+
+fn is_executable(path: &Path) -> bool {
+    match fs::metadata(host_path(path)) {
+        Ok(meta) => meta.is_file() && meta.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Looks the executable up in the static table, by full path first and then by name.
+fn known_browser(known: &[Browser], exec: &Path) -> Option<Browser> {
+    let name = file_name(exec);
+
+    known
+        .iter()
+        .find(|b| b.test_path() == exec)
+        .or_else(|| known.iter().find(|b| file_name(b.test_path()) == name))
+        .cloned()
+}
+
+fn browser_for(known: &[Browser], exec: &Path, fallback_name: &str) -> Option<Browser> {
+    let exec_str = exec.to_str()?;
+
+    if let Some(known) = known_browser(known, exec) {
+        let mut browser = Browser::new(known._type, &known.name, exec_str, exec_str);
+        browser.command = known.command;
+
+        return Some(browser);
+    }
+
+    let _type = classify(&file_name(exec)).or_else(|| classify(fallback_name))?;
+
+    Some(Browser::new(_type, fallback_name, exec_str, exec_str))
+}
+
+fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+
+    if let Ok(path) = std::env::var("PATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+
+    let home = home_dir();
+
+    dirs.extend([
+        PathBuf::from("/usr/bin"),
+        PathBuf::from("/usr/local/bin"),
+        PathBuf::from("/snap/bin"),
+        PathBuf::from("/run/current-system/sw/bin"),
+        PathBuf::from("/nix/var/nix/profiles/default/bin"),
+        home.join(".nix-profile/bin"),
+        home.join(".local/bin"),
+    ]);
+
+    // browsers shipped as tarballs usually live in /opt/<name>/
+    if let Ok(entries) = fs::read_dir(host_path(Path::new("/opt"))) {
+        for entry in entries.flatten() {
+            dirs.push(Path::new("/opt").join(entry.file_name()));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    dirs.retain(|dir| seen.insert(dir.clone()));
+
+    dirs
+}
+
+fn scan_path(known: &[Browser]) -> Vec<Browser> {
+    let mut browsers = Vec::new();
+
+    for dir in search_dirs() {
+        let entries = match fs::read_dir(host_path(&dir)) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            let exec = dir.join(entry.file_name());
+
+            if !is_executable(&exec) {
+                continue;
+            }
+
+            // unknown executables found on PATH are too ambiguous to guess
+            if known_browser(known, &exec).is_some() {
+                if let Some(browser) = browser_for(known, &exec, "") {
+                    browsers.push(browser);
+                }
+            }
+        }
+    }
+
+    browsers
+}
+
+pub fn flatpak_installations() -> Vec<PathBuf> {
+    let mut installations = vec![
+        PathBuf::from("/var/lib/flatpak"),
+        home_dir().join(".local/share/flatpak"),
+    ];
+
+    // custom installations, see flatpak-installation(5)
+    if let Ok(entries) = fs::read_dir(host_path(Path::new("/etc/flatpak/installations.d"))) {
+        for entry in entries.flatten() {
+            if let Ok(content) = fs::read_to_string(entry.path()) {
+                for line in content.lines() {
+                    if let Some(path) = line.trim().strip_prefix("Path=") {
+                        installations.push(PathBuf::from(path));
+                    }
+                }
+            }
+        }
+    }
+
+    installations
+}
+
+fn scan_flatpaks(known: &[Browser]) -> Vec<Browser> {
+    let mut browsers = Vec::new();
+
+    for installation in flatpak_installations() {
+        let exports = installation.join("exports/bin");
+        let applications = installation.join("exports/share/applications");
+
+        if let Ok(entries) = fs::read_dir(host_path(&exports)) {
+            for entry in entries.flatten() {
+                let app_id = file_name(&entry.path());
+
+                // app ids alone are too ambiguous, only browsers are classified
+                let desktop_file = applications.join(format!("{}.desktop", app_id));
+                let Some((name, _)) = read_desktop_file(&host_path(&desktop_file)) else {
+                    continue;
+                };
+
+                if let Some(browser) = browser_for(known, &exports.join(&app_id), &name) {
+                    browsers.push(browser);
+                }
+            }
+        }
+    }
+
+    browsers
+}
+
+fn scan_appimages(known: &[Browser]) -> Vec<Browser> {
+    let mut browsers = Vec::new();
+    let home = home_dir();
+
+    for dir in [
+        home.join("Applications"),
+        home.join(".local/bin"),
+        PathBuf::from("/opt"),
+    ] {
+        if let Ok(entries) = fs::read_dir(host_path(&dir)) {
+            for entry in entries.flatten() {
+                let path = dir.join(entry.file_name());
+                let name = file_name(&path);
+
+                if !name.to_lowercase().ends_with(".appimage") || !is_executable(&path) {
+                    continue;
+                }
+
+                let display_name = name
+                    .split(['-', '_', '.'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+
+                if let Some(browser) =
+                    browser_for(known, &path, &format!("{} (AppImage)", display_name))
+                {
+                    browsers.push(browser);
+                }
+            }
+        }
+    }
+
+    browsers
+}
+
+fn desktop_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    match std::env::var("XDG_DATA_HOME") {
+        Ok(data_home) => dirs.push(PathBuf::from(data_home)),
+        Err(_) => dirs.push(home_dir().join(".local/share")),
+    }
+
+    match std::env::var("XDG_DATA_DIRS") {
+        Ok(data_dirs) => dirs.extend(std::env::split_paths(&data_dirs)),
+        Err(_) => dirs.extend([
+            PathBuf::from("/usr/local/share"),
+            PathBuf::from("/usr/share"),
+        ]),
+    }
+
+    for installation in flatpak_installations() {
+        dirs.push(installation.join("exports/share"));
+    }
+
+    dirs.push(PathBuf::from("/var/lib/snapd/desktop"));
+    dirs.push(PathBuf::from("/run/current-system/sw/share"));
+    dirs.push(home_dir().join(".nix-profile/share"));
+
+    let mut seen = HashSet::new();
+    dirs.retain(|dir| seen.insert(dir.clone()));
+
+    dirs.into_iter()
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// Returns the name and the command of a desktop file in the `WebBrowser` category.
+fn read_desktop_file(path: &Path) -> Option<(String, String)> {
+    let file = fs::File::open(path).ok()?;
+    let reader = io::BufReader::new(file);
+
+    let mut in_entry = false;
+    let mut name = String::new();
+    let mut exec = String::new();
+    let mut is_browser = false;
+
+    for line in reader.lines().map_while(Result::ok) {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+
+        if !in_entry {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            match key.trim() {
+                "Name" => name = value.trim().to_string(),
+                "Exec" => exec = value.trim().to_string(),
+                "Categories" => is_browser = value.split(';').any(|c| c == "WebBrowser"),
+                _ => {}
+            }
+        }
+    }
+
+    if is_browser && !name.is_empty() && !exec.is_empty() {
+        Some((name, exec))
+    } else {
+        None
+    }
+}
+
+/// Resolves the executable a desktop file `Exec` line starts.
+fn resolve_exec(exec: &str) -> Option<PathBuf> {
+    let mut tokens = exec
+        .split_whitespace()
+        .map(|token| token.trim_matches('"'))
+        .skip_while(|token| *token == "env" || token.contains('='));
+
+    let program = tokens.next()?;
+
+    if program == "flatpak" || program.ends_with("/flatpak") {
+        let app_id = tokens
+            .skip_while(|token| *token != "run")
+            .skip(1)
+            .find(|token| !token.starts_with('-'))?;
+
+        return flatpak_installations()
+            .into_iter()
+            .map(|installation| installation.join("exports/bin").join(app_id))
+            .find(|path| host_path(path).exists());
+    }
+
+    let program = Path::new(program);
+
+    if program.is_absolute() {
+        return is_executable(program).then(|| program.to_path_buf());
+    }
+
+    search_dirs()
+        .into_iter()
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
+
+fn scan_desktop_files(known: &[Browser]) -> Vec<Browser> {
+    let mut browsers = Vec::new();
+
+    for dir in desktop_dirs() {
+        let entries = match fs::read_dir(host_path(&dir)) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path.extension().and_then(|ext| ext.to_str()) != Some("desktop") {
+                continue;
+            }
+
+            if let Some((name, exec)) = read_desktop_file(&path) {
+                if let Some(exec_path) = resolve_exec(&exec) {
+                    let hint = format!("{} {}", file_name(&path), name);
+
+                    let browser = match known_browser(known, &exec_path) {
+                        Some(_) => browser_for(known, &exec_path, &name),
+                        None => classify(&hint).and_then(|_type| {
+                            let exec_str = exec_path.to_str()?;
+                            Some(Browser::new(_type, &name, exec_str, exec_str))
+                        }),
+                    };
+
+                    if let Some(browser) = browser {
+                        browsers.push(browser);
+                    }
+                }
+            }
+        }
+    }
+
+    browsers
+}
+
+/// Finds installed browsers from the browser definitions, on `PATH`, in desktop
+/// files, flatpak installations, snap and AppImage locations.
+pub fn detect_browsers() -> Vec<Browser> {
+    let known = known_browsers();
+
+    // definitions point at their install location directly
+    let mut detected: Vec<Browser> = known
+        .iter()
+        .filter(|browser| host_path(browser.test_path()).exists())
+        .cloned()
+        .collect();
+    detected.extend(scan_path(&known));
+    detected.extend(scan_flatpaks(&known));
+    detected.extend(scan_desktop_files(&known));
+    detected.extend(scan_appimages(&known));
+
+    let mut seen_ids = HashSet::new();
+    let mut seen_paths = HashSet::new();
+    detected.retain(|browser| {
+        let test = fs::canonicalize(host_path(browser.test_path()))
+            .unwrap_or_else(|_| browser.test_path().to_path_buf());
+
+        seen_ids.insert(browser.id.clone()) && seen_paths.insert(test)
+    });
+
+    detected
+}
@@ -2,9 +2,10 @@
 
 use std::{
     ffi::OsStr,
-    fs::{self, copy, create_dir_all, File, remove_dir_all, remove_file},
+    future::Future,
+    fs::{self, copy, create_dir_all, File, remove_file},
     io::{self, BufRead, Cursor, Read, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
     sync::Mutex,
 };
@@ -20,13 +21,29 @@ use usvg::fontdb;
 use walkdir::WalkDir;
 
 use crate::{
+    browser_detection::detect_browsers,
+    engines::{
+        download_dir,
+        environment::{env_prefix, DisplayBackend},
+        firefox_chrome::FirefoxChrome,
+        firefox_policies,
+        ice_profile,
+        permissions::Permissions,
+        CONTENT_CSS_FILE, USER_SCRIPT_FILE,
+        proxy::{Proxy, ProxyMode},
+        Engine, EngineOption, Retention, WindowGeometry, WindowMode,
+    },
     icon_cache::IconCache,
     iconpicker,
-    supported_browsers::{flatpak_browsers, native_browsers},
+    profiles::{
+        launch_script, migrate_profile, needs_override, override_command,
+        remove_launch_files, restore_seed,
+    },
 };
 
 lazy_static::lazy_static! {
     static ref ICON_CACHE: Mutex<IconCache> = Mutex::new(IconCache::new());
+    static ref BROWSERS: Mutex<Option<Vec<Browser>>> = Mutex::new(None);
 }
 
 pub fn icon_cache_get(name: &'static str, size: u16) -> widget::icon::Icon {
@@ -59,6 +76,12 @@ pub fn desktop_filepath(filename: &str) -> PathBuf {
     home.join(filename)
 }
 
+pub fn config_dir() -> PathBuf {
+    let mut config = dirs::config_dir().unwrap_or_else(|| home_dir().join(".config"));
+    config.push("io.github.elevenhsoft.WebApps");
+    config
+}
+
 pub fn icons_location() -> PathBuf {
     match std::env::var("FLATPAK_ID") {
         Ok(_) => {
@@ -90,6 +113,30 @@ pub struct WebAppLauncher {
     pub isolate_profile: bool,
     pub navbar: bool,
     pub is_incognito: bool,
+    pub firefox_prefs: Vec<(String, String)>,
+    pub firefox_extensions: Vec<String>,
+    pub firefox_chrome: FirefoxChrome,
+    pub content_css: String,
+    pub user_script: String,
+    pub chromium_prefs: Vec<(String, String)>,
+    pub chromium_extensions: Vec<String>,
+    pub window_mode: WindowMode,
+    pub window_geometry: WindowGeometry,
+    pub user_agent: String,
+    pub proxy: Proxy,
+    /// Scale of the web app's pages in percent.
+    pub zoom: u32,
+    pub force_dark: bool,
+    pub download_dir: String,
+    pub download_ask: bool,
+    /// Language tags in order of preference, the first is also the UI language.
+    pub languages: Vec<String>,
+    pub dictionaries: Vec<String>,
+    pub permissions: Permissions,
+    pub retention: Retention,
+    /// Variables set for the browser, in the order they are written.
+    pub environment: Vec<(String, String)>,
+    pub backend: DisplayBackend,
 }
 
 impl WebAppLauncher {
@@ -137,11 +184,33 @@ impl WebAppLauncher {
             isolate_profile,
             navbar,
             is_incognito,
+            firefox_prefs: Vec::new(),
+            firefox_extensions: Vec::new(),
+            firefox_chrome: FirefoxChrome::default(),
+            content_css: String::new(),
+            user_script: String::new(),
+            chromium_prefs: Vec::new(),
+            chromium_extensions: Vec::new(),
+            window_mode: WindowMode::default(),
+            window_geometry: WindowGeometry::default(),
+            user_agent: String::new(),
+            proxy: Proxy::default(),
+            zoom: 100,
+            force_dark: false,
+            download_dir: String::new(),
+            download_ask: false,
+            languages: Vec::new(),
+            dictionaries: Vec::new(),
+            permissions: Permissions::default(),
+            retention: Retention::default(),
+            environment: Vec::new(),
+            backend: DisplayBackend::default(),
         }
     }
 
     pub fn read(path: PathBuf, codename: String) -> Result<WebAppLauncher, Error> {
         let file = File::open(&path)?;
+        let mut browser_id = String::new();
         let mut browser_name = String::new();
         let mut name = String::new();
         let mut icon = String::new();
@@ -154,6 +223,28 @@ impl WebAppLauncher {
         let mut isolate_profile = false;
         let mut navbar = false;
         let mut is_incognito = false;
+        let mut firefox_prefs = Vec::new();
+        let mut firefox_extensions = Vec::new();
+        let mut firefox_chrome = FirefoxChrome::default();
+        let mut show_tabs = None;
+        let mut content_css = String::new();
+        let mut user_script = String::new();
+        let mut chromium_prefs = Vec::new();
+        let mut chromium_extensions = Vec::new();
+        let mut window_mode = WindowMode::default();
+        let mut window_geometry = WindowGeometry::default();
+        let mut user_agent = String::new();
+        let mut proxy = Proxy::default();
+        let mut zoom = 100;
+        let mut force_dark = false;
+        let mut download_dir = String::new();
+        let mut download_ask = false;
+        let mut languages = Vec::new();
+        let mut dictionaries = Vec::new();
+        let mut permissions = Permissions::default();
+        let mut retention = Retention::default();
+        let mut environment = Vec::new();
+        let mut backend = DisplayBackend::default();
 
         let reader = io::BufReader::new(file);
 
@@ -162,65 +253,89 @@ impl WebAppLauncher {
         for line_result in reader.lines() {
             match line_result {
                 Ok(line) => {
-                    if line.contains("StartupWMClass=WebApp")
-                        || line.contains("StartupWMClass=Chromium")
-                        || line.contains("StartupWMClass=ICE-SSB")
-                    {
-                        is_webapp = true;
-                    };
-
-                    if line.contains("Name=") {
-                        name = line.replace("Name=", "");
-                    };
-
-                    if line.contains("Icon=") {
-                        icon = line.replace("Icon=", "");
-                    };
-
-                    if line.contains("Exec=") {
-                        exec = line.replace("Exec=", "");
-                    };
-
-                    if line.contains("Categories=") {
-                        category = line
-                            .replace("Categories=", "")
-                            .replace("GTK;", "")
-                            .replace(';', "");
-                    };
-
-                    if line.contains("X-WebApp-Browser=") {
-                        browser_name = line.replace("X-WebApp-Browser=", "");
-                    };
-
-                    if line.contains("X-WebApp-URL=") {
-                        url = line.replace("X-WebApp-URL=", "");
-                    };
-
-                    if line.contains("X-WebApp-CustomParameters=") {
-                        custom_parameters = line.replace("X-WebApp-CustomParameters=", "");
-                    };
-
-                    if line.contains("X-WebApp-Isolated=") {
-                        isolate_profile = line.replace("X-WebApp-Isolated=", "") == "true"
-                    };
-
-                    if line.contains("X-WebApp-Navbar=") {
-                        navbar = line.replace("X-WebApp-Navbar=", "") == "true"
-                    };
-
-                    if line.contains("X-WebApp-PrivateWindow=") {
-                        is_incognito = line.replace("X-WebApp-PrivateWindow=", "") == "true"
+                    // values may contain anything, so only the key before the first `=` counts
+                    let Some((key, value)) = line.split_once('=') else {
+                        continue;
                     };
+                    let value = value.to_string();
+
+                    match key {
+                        "StartupWMClass" => {
+                            is_webapp = value.starts_with("WebApp")
+                                || value.starts_with("Chromium")
+                                || value.starts_with("ICE-SSB")
+                                || value.starts_with("org.gnome.Epiphany.WebApp")
+                        }
+                        "Name" => name = value,
+                        "Icon" => icon = value,
+                        "Exec" => exec = value,
+                        "Categories" => category = value.replace("GTK;", "").replace(';', ""),
+                        "X-WebApp-Browser" => browser_name = value,
+                        "X-WebApp-BrowserId" => browser_id = value,
+                        "X-WebApp-URL" => url = value,
+                        "X-WebApp-CustomParameters" => custom_parameters = value,
+                        "X-WebApp-Isolated" => isolate_profile = value == "true",
+                        "X-WebApp-Navbar" => navbar = value == "true",
+                        "X-WebApp-PrivateWindow" => is_incognito = value == "true",
+                        "X-WebApp-FirefoxPrefs" => firefox_prefs = decode_pairs(&value),
+                        "X-WebApp-FirefoxExtensions" => firefox_extensions = decode_list(&value),
+                        "X-WebApp-FirefoxTabs" => show_tabs = Some(value == "true"),
+                        "X-WebApp-FirefoxMiniToolbar" => {
+                            firefox_chrome.mini_toolbar = value == "true"
+                        }
+                        "X-WebApp-FirefoxReadOnlyUrl" => {
+                            firefox_chrome.readonly_url = value == "true"
+                        }
+                        "X-WebApp-FirefoxTitleColor" => firefox_chrome.title_color = value,
+                        "X-WebApp-FirefoxCss" => firefox_chrome.custom_css = decode_list(&value),
+                        // written by older versions, the files in the profile win
+                        "X-WebApp-ContentCss" => content_css = decode_list(&value).join("\n"),
+                        "X-WebApp-UserScript" => user_script = decode_list(&value).join("\n"),
+                        "X-WebApp-ChromiumPrefs" => chromium_prefs = decode_pairs(&value),
+                        "X-WebApp-ChromiumExtensions" => chromium_extensions = decode_list(&value),
+                        "X-WebApp-WindowMode" => window_mode = WindowMode::parse(&value),
+                        "X-WebApp-WindowSize" => {
+                            window_geometry.size = WindowGeometry::parse_size(&value)
+                        }
+                        "X-WebApp-WindowPosition" => {
+                            window_geometry.position = WindowGeometry::parse_position(&value)
+                        }
+                        "X-WebApp-Maximized" => window_geometry.maximized = value == "true",
+                        "X-WebApp-UserAgent" => user_agent = value,
+                        "X-WebApp-Proxy" => proxy.mode = ProxyMode::parse(&value),
+                        "X-WebApp-ProxyHost" => proxy.host = value,
+                        "X-WebApp-ProxyPort" => proxy.port = value.parse().ok(),
+                        "X-WebApp-ProxyBypass" => proxy.bypass = decode_list(&value),
+                        "X-WebApp-ProxyPac" => proxy.pac_url = value,
+                        "X-WebApp-Zoom" => zoom = value.parse().unwrap_or(100),
+                        "X-WebApp-ForceDark" => force_dark = value == "true",
+                        "X-WebApp-DownloadDir" => download_dir = value,
+                        "X-WebApp-DownloadAsk" => download_ask = value == "true",
+                        "X-WebApp-Languages" => languages = decode_list(&value),
+                        "X-WebApp-Dictionaries" => dictionaries = decode_list(&value),
+                        "X-WebApp-Permissions" => {
+                            permissions = Permissions::from_pairs(&decode_pairs(&value))
+                        }
+                        "X-WebApp-Retention" => retention = Retention::parse(&value),
+                        "X-WebApp-Environment" => environment = decode_pairs(&value),
+                        "X-WebApp-Backend" => backend = DisplayBackend::parse(&value),
+                        _ => {}
+                    }
                 }
                 Err(e) => eprintln!("Error reading line: {}", e),
             }
         }
 
+        // the nav bar used to come with tabs
+        firefox_chrome.show_tabs = show_tabs.unwrap_or(navbar);
+
         if is_webapp && !name.is_empty() && !icon.is_empty() {
             is_valid = true
         }
 
-        let web_browser = Browser::web_browser(browser_name);
+        // launchers written before browser ids only know the display name
+        let web_browser =
+            Browser::from_id(&browser_id).or_else(|| Browser::web_browser(browser_name));
 
         match web_browser {
             Some(web_browser) => {
@@ -230,7 +345,7 @@ impl WebAppLauncher {
                     }
                 });
 
-                Ok(WebAppLauncher {
+                let mut launcher = WebAppLauncher {
                     path,
                     codename,
                     web_browser,
@@ -245,173 +360,196 @@ impl WebAppLauncher {
                     isolate_profile,
                     navbar,
                     is_incognito,
-                })
+                    firefox_prefs,
+                    firefox_extensions,
+                    firefox_chrome,
+                    content_css,
+                    user_script,
+                    chromium_prefs,
+                    chromium_extensions,
+                    window_mode,
+                    window_geometry,
+                    user_agent,
+                    proxy,
+                    zoom,
+                    force_dark,
+                    download_dir,
+                    download_ask,
+                    languages,
+                    dictionaries,
+                    permissions,
+                    retention,
+                    environment,
+                    backend,
+                };
+
+                if let Some(engine) = launcher.web_browser._type.engine() {
+                    engine.parse_launcher(&mut launcher);
+                    launcher.read_content_files(engine);
+                }
+
+                Ok(launcher)
             }
             None => Err(anyhow!("Cannot read web app launcher.")),
         }
     }
 
-    fn create_firefox_userjs(&self, path: PathBuf) -> bool {
-        let content = include_bytes!("../data/runtime/firefox/profile/user.js");
-
-        let mut file = File::create(&path)
-            .unwrap_or_else(|_| panic!("failed to create user.js in {:?}", path));
-
-        file.write_all(content).is_ok()
-    }
-
-    fn create_user_chrome_css(&self, path: PathBuf, create_navbar: bool) -> bool {
-        let user_chrome_css =
-            include_bytes!("../data/runtime/firefox/profile/chrome/userChrome.css");
-
-        let mut file = File::create(&path)
-            .unwrap_or_else(|_| panic!("cant create userChrome.css in {:?}", path));
+    /// Fills the user defined command line template of the browser.
+    fn exec_template(&self, engine: &dyn Engine, template: &str) -> String {
+        let profile_path = engine
+            .profile_path(self)
+            .unwrap_or_else(|| ice_profile(&self.codename));
 
-        if create_navbar {
-            file.write_all(b"").is_ok()
+        let private = if self.is_incognito {
+            engine.private_flag(self)
         } else {
-            file.write_all(user_chrome_css).is_ok()
-        }
-    }
-
-    fn exec_firefox(&self, fork: &str) -> String {
-        let mut profile_dir = home_dir();
-        if fork == "firefox" {
-            profile_dir.push(".var/app/org.mozilla.firefox/data/ice/firefox");
-        } else if fork == "librewolf" {
-            profile_dir.push(".var/app/io.gitlab.librewolf-community/data/ice/librewolf");
-        } else if fork == "waterfox" {
-            profile_dir.push(".var/app/net.waterfox.waterfox/data/ice/waterfox");
+            ""
         };
 
-        let profile_path = profile_dir.join(&self.codename);
-        let user_js_path = profile_path.join("user.js");
-        let mut user_chrome_css = profile_path.join("chrome");
-
-        create_dir_all(&profile_path)
-            .unwrap_or_else(|_| panic!("cant create profile dir in {:?}", &profile_path));
-        create_dir_all(&user_chrome_css)
-            .unwrap_or_else(|_| panic!("cant create chrome dir in {:?}", &user_chrome_css));
-
-        user_chrome_css = user_chrome_css.join("userChrome.css");
+        let exec_string = template
+            .replace("{exec}", &self.exec)
+            .replace("{url}", &self.url)
+            .replace("{class}", &engine.wm_class(self))
+            .replace("{profile}", profile_path.to_str().unwrap())
+            .replace("{private}", private)
+            .replace("{args}", &self.custom_parameters);
 
-        self.create_firefox_userjs(user_js_path);
-        self.create_user_chrome_css(user_chrome_css, self.navbar);
-
-        let profile_path = profile_path.to_str().unwrap();
+        exec_string
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
 
-        let mut exec_string = format!(
-            "{} --class WebApp-{} --name WebApp-{} --profile {} --no-remote ",
-            self.exec, self.codename, self.codename, profile_path
-        );
+    fn read_content_files(&mut self, engine: &dyn Engine) {
+        let (css_file, script_file) = match engine.content_dir(self) {
+            Some(dir) => (dir.join(CONTENT_CSS_FILE), dir.join(USER_SCRIPT_FILE)),
+            None => match engine.content_files(self) {
+                Some(files) => files,
+                None => return,
+            },
+        };
 
-        if self.is_incognito {
-            exec_string.push_str("--private-window ");
+        if let Ok(css) = fs::read_to_string(css_file) {
+            self.content_css = css;
         }
 
-        if !self.custom_parameters.is_empty() {
-            exec_string.push_str(&format!("{} ", self.custom_parameters));
+        if let Ok(script) = fs::read_to_string(script_file) {
+            self.user_script = script;
         }
-
-        exec_string.push_str(&self.url);
-
-        exec_string
     }
 
-    fn exec_chromium(&self) -> String {
-        let mut exec_string = format!(
-            "{} --app={} --class=WebApp-{} --name=WebApp-{} ",
-            self.exec, self.url, self.codename, self.codename
-        );
-
-        if self.isolate_profile {
-            let mut profile_dir = PathBuf::new();
-
-            let mut xdg_data_home = home_dir();
-            xdg_data_home.push(".local/share");
-            let ice_dir = xdg_data_home.join("ice");
-            profile_dir.push(ice_dir.join("profiles").join(&self.codename));
+    fn write_content_files(&self, engine: &dyn Engine) -> Result<()> {
+        let Some(content_dir) = engine.content_dir(self) else {
+            return Ok(());
+        };
 
-            let profile_path = profile_dir.to_str().unwrap();
-            exec_string.push_str(&format!("--user-data-dir={} ", profile_path));
-        }
+        for (file, text) in [
+            (CONTENT_CSS_FILE, &self.content_css),
+            (USER_SCRIPT_FILE, &self.user_script),
+        ] {
+            let path = content_dir.join(file);
 
-        if self.is_incognito {
-            if self.web_browser.name.starts_with("Microsoft Edge") {
-                exec_string.push_str("--inprivate ");
+            if text.trim().is_empty() {
+                let _ = remove_file(path);
             } else {
-                exec_string.push_str("--incognito ");
+                create_dir_all(&content_dir)?;
+                fs::write(path, text)?;
             }
         }
 
-        if !self.custom_parameters.is_empty() {
-            exec_string.push_str(&format!("{} ", self.custom_parameters));
-        }
-
-        exec_string
+        Ok(())
     }
 
-    fn exec_falkon(&self) -> String {
-        let mut exec_string = String::new();
+    fn exec_string(&self, engine: &dyn Engine) -> String {
+        let exec_string = if self.web_browser.command.is_empty() {
+            engine.exec_string(self)
+        } else {
+            self.exec_template(engine, &self.web_browser.command)
+        };
 
-        if self.isolate_profile {
-            let mut profile_dir = PathBuf::new();
+        let exec_string = match engine.profile_path(self) {
+            Some(profile_path) if engine.options().contains(&EngineOption::Retention) => {
+                launch_script(
+                    &exec_string,
+                    &profile_path,
+                    self.retention,
+                    &engine.cache_dirs(self),
+                )
+            }
+            _ => exec_string,
+        };
 
-            let mut xdg_data_home = home_dir();
-            xdg_data_home.push(".local/share");
-            let ice_dir = xdg_data_home.join("ice");
-            profile_dir.push(ice_dir.join("profiles").join(&self.codename));
+        // the user's variables come last and win over the backend's
+        let mut environment = engine.backend_env(self.backend);
+        environment.extend(self.environment.iter().cloned());
 
-            let profile_path = profile_dir.to_str().unwrap();
+        format!("{}{}", env_prefix(&environment), exec_string)
+    }
 
-            exec_string = format!(
-                "{} --portable --wmclass WebApp-{} --profile {} ",
-                self.exec, self.codename, profile_path
-            );
-        }
+    /// Writes the launcher and prepares its profile. `previous` is the
+    /// launcher this one replaces when editing.
+    pub fn create(&self, previous: Option<&WebAppLauncher>) -> Result<()> {
+        let engine = self
+            .web_browser
+            ._type
+            .engine()
+            .ok_or_else(|| anyhow!("No browser selected."))?;
+
+        if let Some(profile_path) = engine.profile_path(self) {
+            for legacy in engine.legacy_profiles(self) {
+                if let Err(e) = migrate_profile(&legacy, &profile_path) {
+                    tracing::error!("Cannot migrate profile {:?}: {}", legacy, e);
+                }
+            }
 
-        if self.is_incognito {
-            exec_string.push_str("--private-browsing ");
-        }
+            let was_ephemeral =
+                matches!(previous, Some(previous) if previous.retention == Retention::Ephemeral);
 
-        if !self.custom_parameters.is_empty() {
-            exec_string.push_str(&format!("{} ", self.custom_parameters));
+            // a profile that stops being ephemeral keeps what it started with, not
+            // what the last session left
+            if was_ephemeral && self.retention != Retention::Ephemeral {
+                if let Err(e) = restore_seed(&profile_path) {
+                    tracing::error!("Cannot restore profile {:?}: {}", profile_path, e);
+                }
+            }
         }
 
-        exec_string.push_str(&format!("--no-remote --current-tab {}", self.url));
-
-        exec_string
-    }
+        if let Some(download_dir) = download_dir(self) {
+            if let Err(e) = create_dir_all(&download_dir) {
+                tracing::error!("Cannot create download directory: {}", e);
+            }
 
-    fn exec_string(&self) -> String {
-        match self.web_browser._type {
-            BrowserType::Firefox => self.exec_firefox("firefox"),
-            BrowserType::FirefoxFlatpak => self.exec_firefox("firefox"),
-            BrowserType::Librewolf => self.exec_firefox("librewolf"),
-            BrowserType::WaterfoxFlatpak => self.exec_firefox("waterfox"),
-            BrowserType::Chromium => self.exec_chromium(),
-            BrowserType::Falkon => self.exec_falkon(),
-            _ => String::new(),
+            // a lasting hole in the browser's sandbox is for the user to decide
+            if let Some(app_id) = needs_override(&self.web_browser, &download_dir) {
+                tracing::warn!(
+                    "{} may not reach {:?}, to allow it run: {}",
+                    app_id,
+                    download_dir,
+                    override_command(&app_id, &download_dir)
+                );
+            }
         }
-    }
 
-    pub fn create(&self) -> Result<()> {
+        engine.create_profile(self)?;
+        self.write_content_files(engine)?;
+
         let mut output = File::create(&self.path)?;
 
         writeln!(output, "[Desktop Entry]")?;
         writeln!(output, "Version=1.0")?;
         writeln!(output, "Name={}", self.name)?;
         writeln!(output, "Comment=Web App")?;
-        writeln!(output, "Exec={}", self.exec_string())?;
+        writeln!(output, "Exec={}", self.exec_string(engine))?;
         writeln!(output, "Terminal=false")?;
         writeln!(output, "Type=Application")?;
         writeln!(output, "Icon={}", self.icon)?;
         writeln!(output, "Categories=GTK;{};", self.category)?;
         writeln!(output, "MimeType=text/html;text/xml;application/xhtml_xml;")?;
-        writeln!(output, "StartupWMClass=WebApp-{}", self.codename)?;
+        writeln!(output, "StartupWMClass={}", engine.wm_class(self))?;
         writeln!(output, "StartupNotify=true")?;
         writeln!(output, "X-MultipleArgs=false")?;
         writeln!(output, "X-WebApp-Browser={}", self.web_browser.name)?;
+        writeln!(output, "X-WebApp-BrowserId={}", self.web_browser.id)?;
         writeln!(output, "X-WebApp-URL={}", self.url)?;
         writeln!(output, "X-WebApp-Navbar={}", self.navbar)?;
         writeln!(output, "X-WebApp-PrivateWindow={}", self.is_incognito)?;
@@ -421,14 +559,140 @@ impl WebAppLauncher {
             "X-WebApp-CustomParameters={}",
             self.custom_parameters
         )?;
+        writeln!(
+            output,
+            "X-WebApp-FirefoxPrefs={}",
+            encode_pairs(&self.firefox_prefs)
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-FirefoxExtensions={}",
+            encode_list(&self.firefox_extensions)
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-FirefoxTabs={}",
+            self.firefox_chrome.show_tabs
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-FirefoxMiniToolbar={}",
+            self.firefox_chrome.mini_toolbar
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-FirefoxReadOnlyUrl={}",
+            self.firefox_chrome.readonly_url
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-FirefoxTitleColor={}",
+            self.firefox_chrome.title_color
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-FirefoxCss={}",
+            encode_list(&self.firefox_chrome.custom_css)
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-ChromiumPrefs={}",
+            encode_pairs(&self.chromium_prefs)
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-ChromiumExtensions={}",
+            encode_list(&self.chromium_extensions)
+        )?;
+        writeln!(output, "X-WebApp-WindowMode={}", self.window_mode.as_str())?;
+        writeln!(
+            output,
+            "X-WebApp-WindowSize={}",
+            self.window_geometry.size_string()
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-WindowPosition={}",
+            self.window_geometry.position_string()
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-Maximized={}",
+            self.window_geometry.maximized
+        )?;
+        writeln!(output, "X-WebApp-UserAgent={}", self.user_agent)?;
+        writeln!(output, "X-WebApp-Proxy={}", self.proxy.mode.as_str())?;
+        writeln!(output, "X-WebApp-ProxyHost={}", self.proxy.host)?;
+        writeln!(
+            output,
+            "X-WebApp-ProxyPort={}",
+            self.proxy
+                .port
+                .map(|port| port.to_string())
+                .unwrap_or_default()
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-ProxyBypass={}",
+            encode_list(&self.proxy.bypass)
+        )?;
+        writeln!(output, "X-WebApp-ProxyPac={}", self.proxy.pac_url)?;
+        writeln!(output, "X-WebApp-Zoom={}", self.zoom)?;
+        writeln!(output, "X-WebApp-ForceDark={}", self.force_dark)?;
+        writeln!(output, "X-WebApp-DownloadDir={}", self.download_dir)?;
+        writeln!(output, "X-WebApp-DownloadAsk={}", self.download_ask)?;
+        writeln!(
+            output,
+            "X-WebApp-Languages={}",
+            encode_list(&self.languages)
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-Dictionaries={}",
+            encode_list(&self.dictionaries)
+        )?;
+        writeln!(
+            output,
+            "X-WebApp-Permissions={}",
+            encode_pairs(&self.permissions.to_pairs())
+        )?;
+        writeln!(output, "X-WebApp-Retention={}", self.retention.as_str())?;
+        writeln!(
+            output,
+            "X-WebApp-Environment={}",
+            encode_pairs(&self.environment)
+        )?;
+        writeln!(output, "X-WebApp-Backend={}", self.backend.as_str())?;
 
         Ok(())
     }
 
-    pub fn delete(&self) -> Result<()> {
-        let exist = self.path.as_path().exists();
+    /// Downloads the web app's Firefox extensions, which may take a while, so
+    /// the GUI waits for it apart from `create`.
+    pub fn install_extensions(&self) -> impl Future<Output = Result<(), String>> + Send + 'static {
+        let profile_path = self
+            .web_browser
+            ._type
+            .engine()
+            .filter(|engine| engine.options().contains(&EngineOption::FirefoxExtensions))
+            .and_then(|engine| engine.profile_path(self));
+        let extensions = self.firefox_extensions.clone();
+
+        async move {
+            match profile_path {
+                Some(profile_path) => {
+                    firefox_policies::install_extensions(profile_path, extensions)
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+                None => Ok(()),
+            }
+        }
+    }
 
-        match exist {
+    /// Removes the desktop file only, keeping the browser profile.
+    pub fn delete_entry(&self) -> Result<()> {
+        match self.path.as_path().exists() {
             true => {
                 remove_file(&self.path)?;
             }
@@ -437,31 +701,71 @@ impl WebAppLauncher {
             }
         }
 
-        let mut profile_dir = home_dir();
+        Ok(())
+    }
 
-        match self.web_browser._type {
-            BrowserType::FirefoxFlatpak => {
-                profile_dir.push(".var/app/org.mozilla.firefox/data/ice/firefox")
-            }
-            BrowserType::Librewolf => {
-                profile_dir.push(".var/app/io.gitlab.librewolf-community/data/ice/librewolf")
-            }
-            BrowserType::WaterfoxFlatpak => {
-                profile_dir.push(".var/app/net.waterfox.waterfox/data/ice/waterfox")
-            }
-            _ => {}
-        };
+    pub fn delete(&self) -> Result<()> {
+        self.delete_entry()?;
 
-        let profile_path = profile_dir.join(&self.codename);
+        if let Some(engine) = self.web_browser._type.engine() {
+            if let Some(profile_path) = engine.profile_path(self) {
+                remove_launch_files(&profile_path);
+            }
 
-        if remove_dir_all(profile_path).is_ok() {
-            tracing::info!("Removed firefox profile directory.");
-        };
+            engine.remove_profile(self);
+        }
 
         Ok(())
     }
 }
 
+/// Joins items into one desktop entry value, escaping `;` and `\`.
+pub fn encode_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("{};", item.replace('\\', "\\\\").replace(';', "\\;")))
+        .collect()
+}
+
+pub fn decode_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => item.extend(chars.next()),
+            ';' => items.push(std::mem::take(&mut item)),
+            _ => item.push(c),
+        }
+    }
+
+    if !item.is_empty() {
+        items.push(item);
+    }
+
+    items
+}
+
+pub fn encode_pairs(pairs: &[(String, String)]) -> String {
+    let items: Vec<String> = pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+
+    encode_list(&items)
+}
+
+pub fn decode_pairs(value: &str) -> Vec<(String, String)> {
+    decode_list(value)
+        .into_iter()
+        .filter_map(|item| {
+            item.split_once('=')
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
 pub fn get_webapps() -> Vec<Result<WebAppLauncher>> {
     let mut webapps = Vec::new();
 
@@ -501,19 +805,113 @@ pub enum BrowserType {
     WaterfoxFlatpak,
     Chromium,
     Falkon,
+    Epiphany,
+    EpiphanyFlatpak,
+    Qutebrowser,
+}
+
+impl BrowserType {
+    pub fn from_engine(engine: &str) -> Option<Self> {
+        match engine {
+            "firefox" => Some(BrowserType::Firefox),
+            "firefox-flatpak" => Some(BrowserType::FirefoxFlatpak),
+            "librewolf-flatpak" => Some(BrowserType::Librewolf),
+            "waterfox-flatpak" => Some(BrowserType::WaterfoxFlatpak),
+            "chromium" => Some(BrowserType::Chromium),
+            "falkon" => Some(BrowserType::Falkon),
+            "epiphany" => Some(BrowserType::Epiphany),
+            "epiphany-flatpak" => Some(BrowserType::EpiphanyFlatpak),
+            "qutebrowser" => Some(BrowserType::Qutebrowser),
+            _ => None,
+        }
+    }
 }
 
+pub const ENGINES: [&str; 9] = [
+    "firefox",
+    "firefox-flatpak",
+    "librewolf-flatpak",
+    "waterfox-flatpak",
+    "chromium",
+    "falkon",
+    "epiphany",
+    "epiphany-flatpak",
+    "qutebrowser",
+];
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Browser {
     pub _type: BrowserType,
+    pub id: String,
     pub name: String,
+    pub label: String,
     pub exec: String,
+    pub command: String,
     test: PathBuf,
 }
 
 impl AsRef<str> for Browser {
     fn as_ref(&self) -> &str {
-        &self.name
+        &self.label
+    }
+}
+
+/// Stable id of an installation, made of its kind and install source,
+/// e.g. `flatpak-system:org.mozilla.firefox` or `native:firefox`.
+pub fn browser_id(test: &Path) -> String {
+    let path = test.to_str().unwrap_or_default();
+    let file = test
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+
+    if path.is_empty() {
+        return String::new();
+    }
+
+    if let Some((installation, _)) = path.split_once("/exports/bin/") {
+        let installation = Path::new(installation);
+
+        let source = if installation == Path::new("/var/lib/flatpak") {
+            String::from("flatpak-system")
+        } else if installation == home_dir().join(".local/share/flatpak") {
+            String::from("flatpak-user")
+        } else {
+            let name = installation
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            format!("flatpak-{}", name)
+        };
+
+        format!("{}:{}", source, file)
+    } else if path.starts_with("/snap/") {
+        format!("snap:{}", file)
+    } else if file.to_lowercase().ends_with(".appimage") {
+        format!("appimage:{}", path)
+    } else if test.parent() == Some(Path::new("/usr/bin")) {
+        format!("native:{}", file)
+    } else {
+        format!("native:{}", path)
+    }
+}
+
+fn source_label(id: &str) -> String {
+    let source = id
+        .split_once(':')
+        .map(|(source, _)| source)
+        .unwrap_or_default();
+
+    match source {
+        "native" => String::from("system"),
+        "flatpak-system" => String::from("Flatpak"),
+        "flatpak-user" => String::from("Flatpak, user"),
+        "snap" => String::from("Snap"),
+        "appimage" => String::from("AppImage"),
+        other => match other.strip_prefix("flatpak-") {
+            Some(installation) => format!("Flatpak, {}", installation),
+            None => other.to_string(),
+        },
     }
 }
 
@@ -545,12 +943,31 @@ impl Browser {
 
         let exec = exe_path.to_str().unwrap().to_string();
 
-        Self {
+        let mut browser = Self {
             _type,
+            id: String::new(),
             name,
+            label: String::new(),
             exec,
+            command: String::new(),
             test,
-        }
+        };
+        browser.set_id(browser_id(&browser.test));
+
+        browser
+    }
+
+    /// Changes the id and the label showing the install source next to the name.
+    pub fn set_id(&mut self, id: String) {
+        let source = source_label(&id);
+
+        self.label =
+            if source.is_empty() || self.name.to_lowercase().contains(&source.to_lowercase()) {
+                self.name.clone()
+            } else {
+                format!("{} ({})", self.name, source)
+            };
+        self.id = id;
     }
 
     pub fn web_browser(name: String) -> Option<Browser> {
@@ -558,39 +975,41 @@ impl Browser {
         supported.into_iter().find(|b| b.name == name)
     }
 
+    pub fn from_id(id: &str) -> Option<Browser> {
+        let supported = get_supported_browsers();
+        supported.into_iter().find(|b| !id.is_empty() && b.id == id)
+    }
+
+    pub fn test_path(&self) -> &Path {
+        &self.test
+    }
+
     pub fn is_installed(&self) -> bool {
         !matches!(self._type, BrowserType::NoBrowser)
     }
 }
 
+/// Installed browsers, detected once as scanning the system is slow.
 pub fn get_supported_browsers() -> Vec<Browser> {
-    let mut test_browsers: Vec<Browser> = Vec::new();
-
-    let native_browsers = native_browsers();
-    let flatpak_browsers = flatpak_browsers();
-
-    test_browsers.extend(native_browsers);
-    test_browsers.extend(flatpak_browsers);
+    let mut cache = BROWSERS.lock().unwrap();
 
-    let mut browsers = Vec::new();
+    cache
+        .get_or_insert_with(|| {
+            let mut browsers = detect_browsers();
 
-    for browser in test_browsers {
-        let exists = browser.test.as_path().try_exists();
+            browsers.insert(
+                0,
+                Browser::new(BrowserType::NoBrowser, "Select browser", "", ""),
+            );
 
-        match exists {
-            Ok(found) => match found {
-                true => browsers.push(browser),
-                false => continue,
-            },
-            Err(_) => continue,
-        }
-    }
-    browsers.insert(
-        0,
-        Browser::new(BrowserType::NoBrowser, "Select browser", "", ""),
-    );
+            browsers
+        })
+        .clone()
+}
 
-    browsers
+/// Detects the browsers again on the next lookup, e.g. after definitions changed.
+pub fn refresh_browsers() {
+    *BROWSERS.lock().unwrap() = None;
 }
 
 pub fn get_icon_name_from_url(url: &str) -> String {
@@ -666,14 +1085,7 @@ pub async fn search_user_icons() -> Vec<String> {
 pub async fn download_favicon(url: &str) -> Result<Vec<String>> {
     let mut favicons = Vec::new();
 
-    let content = Client::new()
-        .get(url)
-        .send()
-        .await
-        .expect("sending request")
-        .text()
-        .await
-        .expect("getting content");
+    let content = Client::new().get(url).send().await?.text().await?;
 
     let document = Html::parse_document(&content);
     let head = Selector::parse("head").unwrap();
@@ -703,6 +1115,26 @@ pub async fn download_favicon(url: &str) -> Result<Vec<String>> {
     Ok(favicons)
 }
 
+pub async fn find_theme_color(url: &str) -> Option<String> {
+    let content = Client::new()
+        .get(url)
+        .send()
+        .await
+        .ok()?
+        .text()
+        .await
+        .ok()?;
+
+    let document = Html::parse_document(&content);
+    let meta = Selector::parse("meta").unwrap();
+
+    document
+        .select(&meta)
+        .find(|meta| meta.value().attr("name") == Some("theme-color"))
+        .and_then(|meta| meta.value().attr("content"))
+        .map(|color| color.to_string())
+}
+
 pub fn move_icon(path: String, output_name: String) -> String {
     let user_icons = icons_location().join("MyIcons");
     create_dir_all(user_icons.clone()).expect("cant create icons folder");
@@ -1,13 +1,25 @@
 use cosmic::{
     Command,
     Element, iced::{Alignment, id, Length},
+    iced_widget::{Scrollable, text_editor},
     style,
     theme, widget::{self, Button, Column, Container, dropdown, focus, Row, TextInput, toggler},
 };
 
 use crate::{
     common::{Browser, BrowserType, get_supported_browsers, icon_cache_get, url_valid},
+    engines::{
+        chromium_profile::KNOWN_PREFERENCES,
+        EngineOption,
+        environment::{DisplayBackend, KNOWN_VARIABLES},
+        firefox_chrome::{FirefoxChrome, is_css_color},
+        firefox_prefs::KNOWN_PREFS,
+        permissions::{Permission, Permissions, PermissionState},
+        proxy::{Proxy, ProxyMode},
+        user_agent, Retention, WindowGeometry, WindowMode, ZOOM_LEVELS,
+    },
     gui, iconpicker,
+    languages::{installed_dictionaries, installed_locales},
     warning::{Warning, WarnMessages},
 };
 
@@ -28,7 +40,52 @@ pub struct AppCreator {
     pub app_navbar: bool,
     pub app_incognito: bool,
     pub app_isolated: bool,
+    pub app_firefox_prefs: Vec<(String, String)>,
+    pub app_firefox_extensions: String,
+    pub app_firefox_chrome: FirefoxChrome,
+    pub app_chrome_css: TextArea,
+    pub app_content_css: TextArea,
+    pub app_user_script: TextArea,
+    pub known_prefs: Vec<String>,
+    pub app_chromium_prefs: Vec<(String, String)>,
+    pub known_chromium_prefs: Vec<String>,
+    pub app_chromium_extensions: String,
+    pub app_window_mode: WindowMode,
+    pub window_modes: Vec<String>,
+    pub app_window_width: String,
+    pub app_window_height: String,
+    pub app_window_x: String,
+    pub app_window_y: String,
+    pub app_maximized: bool,
+    pub app_user_agent: String,
+    pub user_agent_presets: Vec<String>,
+    pub app_proxy_mode: ProxyMode,
+    pub proxy_modes: Vec<String>,
+    pub app_proxy_host: String,
+    pub app_proxy_port: String,
+    pub app_proxy_bypass: String,
+    pub app_proxy_pac: String,
+    pub app_zoom: u32,
+    pub zoom_levels: Vec<String>,
+    pub app_force_dark: bool,
+    pub app_download_dir: String,
+    pub app_download_ask: bool,
+    pub app_languages: Vec<String>,
+    pub installed_locales: Vec<String>,
+    pub app_dictionaries: Vec<String>,
+    pub installed_dictionaries: Vec<String>,
+    pub app_permissions: Permissions,
+    pub permission_states: Vec<String>,
+    pub app_retention: Retention,
+    pub retention_modes: Vec<Retention>,
+    pub retention_labels: Vec<String>,
+    pub app_environment: Vec<(String, String)>,
+    pub known_variables: Vec<String>,
+    pub app_backend: DisplayBackend,
+    pub backends: Vec<String>,
     pub selected_icon: Option<iconpicker::Icon>,
+    /// SVG of the monogram offered in the icon picker.
+    pub monogram: Option<String>,
     pub app_browsers: Vec<Browser>,
     pub selected_browser: Option<usize>,
     pub warning: Warning,
@@ -43,6 +100,25 @@ pub enum Message {
     Arguments(String),
     Browser(usize),
     Category(usize),
+    WindowMode(usize),
+    Geometry(Geometry, String),
+    UserAgent(String),
+    UserAgentPreset(usize),
+    ProxyMode(usize),
+    Proxy(ProxyField, String),
+    Zoom(usize),
+    DownloadDir(String),
+    AddChoice(Choices, usize),
+    Permission(Permission, usize),
+    Retention(usize),
+    Backend(usize),
+    PairKey(Pairs, usize, String),
+    PairValue(Pairs, usize, String),
+    KnownPair(Pairs, usize),
+    Extensions(String),
+    ChromiumExtensions(String),
+    TitleColor(String),
+    Edit(Lines, text_editor::Action),
 
     Clicked(Buttons),
 }
@@ -52,18 +128,118 @@ pub enum Buttons {
     Navbar(bool),
     IsolatedProfile(bool),
     Incognito(bool),
+    AddPair(Pairs),
+    RemovePair(Pairs, usize),
+    ShowTabs(bool),
+    MiniToolbar(bool),
+    ReadOnlyUrl(bool),
+    Maximized(bool),
+    ForceDark(bool),
+    DownloadAsk(bool),
+    RemoveChoice(Choices, usize),
+}
+
+/// Key and value lists, e.g. browser preferences.
+#[derive(Debug, Clone, Copy)]
+pub enum Pairs {
+    FirefoxPrefs,
+    ChromiumPrefs,
+    Environment,
+}
+
+/// Multi-line texts, e.g. style sheets.
+#[derive(Debug, Clone, Copy)]
+pub enum Lines {
+    ChromeCss,
+    ContentCss,
+    UserScript,
+}
+
+/// Content of a `text_editor`, which can't be cloned on its own.
+pub struct TextArea(text_editor::Content);
+
+impl TextArea {
+    pub fn with_text(text: &str) -> Self {
+        TextArea(text_editor::Content::with_text(text))
+    }
+
+    pub fn text(&self) -> String {
+        let text = self.0.text();
+
+        // the editor ends its text with a line break
+        match text.strip_suffix('\n') {
+            Some(text) => text.to_string(),
+            None => text,
+        }
+    }
+
+    /// Non-empty lines, as the desktop file lists them.
+    pub fn lines(&self) -> Vec<String> {
+        self.text()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(String::from)
+            .collect()
+    }
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        TextArea(text_editor::Content::new())
+    }
+}
+
+impl Clone for TextArea {
+    fn clone(&self) -> Self {
+        TextArea::with_text(&self.text())
+    }
+}
+
+impl std::fmt::Debug for TextArea {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TextArea").field(&self.text()).finish()
+    }
+}
+
+/// Fields of the window size and position.
+#[derive(Debug, Clone, Copy)]
+pub enum Geometry {
+    Width,
+    Height,
+    X,
+    Y,
+}
+
+/// Ordered lists picked from what the system offers.
+#[derive(Debug, Clone, Copy)]
+pub enum Choices {
+    Languages,
+    Dictionaries,
+}
+
+/// Text fields of the proxy settings.
+#[derive(Debug, Clone, Copy)]
+pub enum ProxyField {
+    Host,
+    Port,
+    Bypass,
+    Pac,
 }
 
 impl AppCreator {
     pub fn new() -> Self {
         let browsers = get_supported_browsers();
         let browser = &browsers[0];
+        let retention_modes = browser
+            ._type
+            .engine()
+            .map(|engine| engine.retention_modes())
+            .unwrap_or_default();
 
         let starting_warns = vec![
             WarnMessages::Info,
             WarnMessages::AppName,
             WarnMessages::AppUrl,
-            WarnMessages::AppIcon,
             WarnMessages::AppBrowser,
         ];
 
@@ -97,7 +273,78 @@ impl AppCreator {
             app_navbar: false,
             app_incognito: false,
             app_isolated: true,
+            app_firefox_prefs: Vec::new(),
+            app_firefox_extensions: String::new(),
+            app_firefox_chrome: FirefoxChrome::default(),
+            app_chrome_css: TextArea::default(),
+            app_content_css: TextArea::default(),
+            app_user_script: TextArea::default(),
+            known_prefs: KNOWN_PREFS.iter().map(|(key, _)| key.to_string()).collect(),
+            app_chromium_prefs: Vec::new(),
+            known_chromium_prefs: KNOWN_PREFERENCES
+                .iter()
+                .map(|(key, _)| key.to_string())
+                .collect(),
+            app_chromium_extensions: String::new(),
+            app_window_mode: WindowMode::default(),
+            window_modes: WindowMode::ALL
+                .iter()
+                .map(|mode| mode.label().to_string())
+                .collect(),
+            app_window_width: String::new(),
+            app_window_height: String::new(),
+            app_window_x: String::new(),
+            app_window_y: String::new(),
+            app_maximized: false,
+            app_user_agent: String::new(),
+            user_agent_presets: user_agent::PRESETS
+                .iter()
+                .map(|(label, _)| label.to_string())
+                .collect(),
+            app_proxy_mode: ProxyMode::default(),
+            proxy_modes: ProxyMode::ALL
+                .iter()
+                .map(|mode| mode.label().to_string())
+                .collect(),
+            app_proxy_host: String::new(),
+            app_proxy_port: String::new(),
+            app_proxy_bypass: String::new(),
+            app_proxy_pac: String::new(),
+            app_zoom: 100,
+            zoom_levels: ZOOM_LEVELS
+                .iter()
+                .map(|zoom| format!("{}%", zoom))
+                .collect(),
+            app_force_dark: false,
+            app_download_dir: String::new(),
+            app_download_ask: false,
+            app_languages: Vec::new(),
+            installed_locales: installed_locales(),
+            app_dictionaries: Vec::new(),
+            installed_dictionaries: installed_dictionaries(),
+            app_permissions: Permissions::default(),
+            permission_states: PermissionState::ALL
+                .iter()
+                .map(|state| state.label().to_string())
+                .collect(),
+            app_retention: Retention::default(),
+            retention_labels: retention_modes
+                .iter()
+                .map(|retention| retention.label().to_string())
+                .collect(),
+            retention_modes,
+            app_environment: Vec::new(),
+            known_variables: KNOWN_VARIABLES
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            app_backend: DisplayBackend::default(),
+            backends: DisplayBackend::ALL
+                .iter()
+                .map(|backend| backend.label().to_string())
+                .collect(),
             selected_icon: None,
+            monogram: None,
             app_browsers: browsers,
             selected_browser: Some(0),
             warning: warn_element,
@@ -143,6 +390,7 @@ impl AppCreator {
                 };
 
                 self.app_browser = browser.clone();
+                self.update_retention_modes();
                 Command::none()
             }
             Message::Category(idx) => {
@@ -150,6 +398,139 @@ impl AppCreator {
                 self.selected_category = idx;
                 Command::none()
             }
+            Message::WindowMode(idx) => {
+                self.app_window_mode = WindowMode::ALL[idx];
+                Command::none()
+            }
+            Message::UserAgent(user_agent) => {
+                self.app_user_agent = user_agent;
+                Command::none()
+            }
+            Message::UserAgentPreset(idx) => {
+                self.app_user_agent = user_agent::PRESETS[idx].1.to_string();
+                Command::none()
+            }
+            Message::DownloadDir(dir) => {
+                self.app_download_dir = dir;
+                self.app_isolated |= self.needs_isolation();
+                Command::none()
+            }
+            Message::AddChoice(choices, idx) => {
+                let choice = match choices {
+                    Choices::Languages => self.installed_locales[idx].clone(),
+                    Choices::Dictionaries => self.installed_dictionaries[idx].clone(),
+                };
+
+                let values = self.choices_mut(choices);
+                if !values.contains(&choice) {
+                    values.push(choice);
+                }
+
+                Command::none()
+            }
+            Message::Permission(permission, idx) => {
+                self.app_permissions
+                    .set(permission, PermissionState::ALL[idx]);
+                self.app_isolated |= self.needs_isolation();
+                Command::none()
+            }
+            Message::Retention(idx) => {
+                self.app_retention = self.retention_modes[idx];
+                self.app_isolated |= self.needs_isolation();
+                Command::none()
+            }
+            Message::Backend(idx) => {
+                self.app_backend = DisplayBackend::ALL[idx];
+                Command::none()
+            }
+            Message::Zoom(idx) => {
+                self.app_zoom = ZOOM_LEVELS[idx];
+                Command::none()
+            }
+            Message::ProxyMode(idx) => {
+                self.app_proxy_mode = ProxyMode::ALL[idx];
+
+                self.app_isolated |= self.needs_isolation();
+
+                Command::none()
+            }
+            Message::Proxy(field, value) => {
+                match field {
+                    ProxyField::Host => self.app_proxy_host = value,
+                    ProxyField::Port => {
+                        self.app_proxy_port = value.chars().filter(char::is_ascii_digit).collect()
+                    }
+                    ProxyField::Bypass => self.app_proxy_bypass = value,
+                    ProxyField::Pac => self.app_proxy_pac = value,
+                }
+
+                Command::none()
+            }
+            Message::Geometry(field, value) => {
+                // only digits, and a minus for positions left of or above the screen
+                let value: String = value
+                    .chars()
+                    .enumerate()
+                    .filter(|(n, c)| {
+                        c.is_ascii_digit()
+                            || (*n == 0 && *c == '-' && matches!(field, Geometry::X | Geometry::Y))
+                    })
+                    .map(|(_, c)| c)
+                    .collect();
+
+                match field {
+                    Geometry::Width => self.app_window_width = value,
+                    Geometry::Height => self.app_window_height = value,
+                    Geometry::X => self.app_window_x = value,
+                    Geometry::Y => self.app_window_y = value,
+                }
+
+                Command::none()
+            }
+            Message::PairKey(pairs, idx, key) => {
+                self.pairs_mut(pairs)[idx].0 = key;
+                Command::none()
+            }
+            Message::PairValue(pairs, idx, value) => {
+                self.pairs_mut(pairs)[idx].1 = value;
+                Command::none()
+            }
+            Message::Extensions(extensions) => {
+                self.app_firefox_extensions = extensions;
+                Command::none()
+            }
+            Message::ChromiumExtensions(extensions) => {
+                self.app_chromium_extensions = extensions;
+                Command::none()
+            }
+            Message::TitleColor(color) => {
+                if color.is_empty() || is_css_color(&color) {
+                    self.warning.remove_warn(WarnMessages::TitleColor);
+                } else {
+                    self.warning.push_warn(WarnMessages::TitleColor);
+                }
+
+                self.app_firefox_chrome.title_color = color;
+                Command::none()
+            }
+            Message::Edit(lines, action) => {
+                self.text_area_mut(lines).0.perform(action);
+                Command::none()
+            }
+            Message::KnownPair(pairs, idx) => {
+                let (key, value) = match pairs {
+                    Pairs::FirefoxPrefs => KNOWN_PREFS[idx],
+                    Pairs::ChromiumPrefs => KNOWN_PREFERENCES[idx],
+                    Pairs::Environment => KNOWN_VARIABLES[idx],
+                };
+
+                let values = self.pairs_mut(pairs);
+                if !values.iter().any(|(k, _)| k == key) {
+                    values.push((key.to_string(), value.to_string()));
+                }
+
+                Command::none()
+            }
 
             Message::Clicked(buttons) => match buttons {
                 Buttons::Navbar(selected) => {
@@ -158,13 +539,59 @@ impl AppCreator {
                     Command::none()
                 }
                 Buttons::IsolatedProfile(selected) => {
-                    self.app_isolated = selected;
+                    self.app_isolated = selected || self.needs_isolation();
 
                     Command::none()
                 }
                 Buttons::Incognito(selected) => {
                     self.app_incognito = selected;
 
+                    Command::none()
+                }
+                Buttons::AddPair(pairs) => {
+                    self.pairs_mut(pairs).push((String::new(), String::new()));
+
+                    Command::none()
+                }
+                Buttons::RemovePair(pairs, idx) => {
+                    self.pairs_mut(pairs).remove(idx);
+
+                    Command::none()
+                }
+                Buttons::ShowTabs(selected) => {
+                    self.app_firefox_chrome.show_tabs = selected;
+
+                    Command::none()
+                }
+                Buttons::MiniToolbar(selected) => {
+                    self.app_firefox_chrome.mini_toolbar = selected;
+
+                    Command::none()
+                }
+                Buttons::ReadOnlyUrl(selected) => {
+                    self.app_firefox_chrome.readonly_url = selected;
+
+                    Command::none()
+                }
+                Buttons::Maximized(selected) => {
+                    self.app_maximized = selected;
+
+                    Command::none()
+                }
+                Buttons::ForceDark(selected) => {
+                    self.app_force_dark = selected;
+
+                    Command::none()
+                }
+                Buttons::RemoveChoice(choices, idx) => {
+                    self.choices_mut(choices).remove(idx);
+
+                    Command::none()
+                }
+                Buttons::DownloadAsk(selected) => {
+                    self.app_download_ask = selected;
+                    self.app_isolated |= self.needs_isolation();
+
                     Command::none()
                 }
             },
@@ -206,6 +633,441 @@ impl AppCreator {
         .into()
     }
 
+    fn pairs_mut(&mut self, pairs: Pairs) -> &mut Vec<(String, String)> {
+        match pairs {
+            Pairs::FirefoxPrefs => &mut self.app_firefox_prefs,
+            Pairs::ChromiumPrefs => &mut self.app_chromium_prefs,
+            Pairs::Environment => &mut self.app_environment,
+        }
+    }
+
+    fn pairs_view(&self, pairs: Pairs) -> Element<gui::Message> {
+        let preference_hints = ("Preference", "Value, e.g. true, 0 or \"text\"");
+
+        let (title, values, known, (key_hint, value_hint)) = match pairs {
+            Pairs::FirefoxPrefs => (
+                "Firefox preferences",
+                &self.app_firefox_prefs,
+                &self.known_prefs,
+                preference_hints,
+            ),
+            Pairs::ChromiumPrefs => (
+                "Chromium preferences",
+                &self.app_chromium_prefs,
+                &self.known_chromium_prefs,
+                preference_hints,
+            ),
+            Pairs::Environment => (
+                "Environment variables",
+                &self.app_environment,
+                &self.known_variables,
+                ("Variable", "Value"),
+            ),
+        };
+
+        let known = dropdown(known, None, move |idx| {
+            gui::Message::Creator(Message::KnownPair(pairs, idx))
+        })
+        .width(Length::Fill);
+        let add = widget::button(icon_cache_get("list-add-symbolic", 16))
+            .on_press(gui::Message::Creator(Message::Clicked(Buttons::AddPair(
+                pairs,
+            ))))
+            .padding(8)
+            .style(style::Button::Icon);
+
+        let mut header = Row::new().spacing(10).align_items(Alignment::Center);
+        header = header.push(widget::text(title));
+        header = header.push(known);
+        header = header.push(add);
+
+        let mut col = Column::new().spacing(10);
+        col = col.push(header);
+
+        for (idx, (key, value)) in values.iter().enumerate() {
+            let key = TextInput::new(key_hint, key)
+                .on_input(move |s| gui::Message::Creator(Message::PairKey(pairs, idx, s)))
+                .width(Length::Fill);
+            let value = TextInput::new(value_hint, value)
+                .on_input(move |s| gui::Message::Creator(Message::PairValue(pairs, idx, s)))
+                .width(Length::Fill);
+            let remove = widget::button(icon_cache_get("edit-delete-symbolic", 16))
+                .on_press(gui::Message::Creator(Message::Clicked(
+                    Buttons::RemovePair(pairs, idx),
+                )))
+                .padding(8)
+                .style(style::Button::Icon);
+
+            let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+            row = row.push(key);
+            row = row.push(value);
+            row = row.push(remove);
+
+            col = col.push(row);
+        }
+
+        col.into()
+    }
+
+    fn chrome_view(&self) -> Element<gui::Message> {
+        let chrome = &self.app_firefox_chrome;
+
+        let tabs = toggler(String::from("Tabs"), chrome.show_tabs, |b| {
+            gui::Message::Creator(Message::Clicked(Buttons::ShowTabs(b)))
+        })
+        .width(Length::Fill);
+        let mini_toolbar = toggler(
+            String::from("Back, forward and reload"),
+            chrome.mini_toolbar,
+            |b| gui::Message::Creator(Message::Clicked(Buttons::MiniToolbar(b))),
+        )
+        .width(Length::Fill);
+        let readonly_url = toggler(String::from("Read-only URL"), chrome.readonly_url, |b| {
+            gui::Message::Creator(Message::Clicked(Buttons::ReadOnlyUrl(b)))
+        })
+        .width(Length::Fill);
+
+        let mut toggles_row = Row::new().spacing(20).align_items(Alignment::Center);
+        toggles_row = toggles_row.push(tabs);
+        toggles_row = toggles_row.push(mini_toolbar);
+        toggles_row = toggles_row.push(readonly_url);
+
+        let title_color = TextInput::new("Title bar color, e.g. #3584e4", &chrome.title_color)
+            .on_input(|s| gui::Message::Creator(Message::TitleColor(s)))
+            .width(Length::Fill);
+
+        let mut col = Column::new().spacing(10);
+        col = col.push(toggles_row);
+        col = col.push(title_color);
+        col = col.push(self.text_area_view(Lines::ChromeCss, "Custom window CSS"));
+
+        col.into()
+    }
+
+    fn text_area_mut(&mut self, lines: Lines) -> &mut TextArea {
+        match lines {
+            Lines::ChromeCss => &mut self.app_chrome_css,
+            Lines::ContentCss => &mut self.app_content_css,
+            Lines::UserScript => &mut self.app_user_script,
+        }
+    }
+
+    fn text_area_view(&self, lines: Lines, title: &str) -> Element<gui::Message> {
+        let content = match lines {
+            Lines::ChromeCss => &self.app_chrome_css,
+            Lines::ContentCss => &self.app_content_css,
+            Lines::UserScript => &self.app_user_script,
+        };
+
+        let editor = text_editor(&content.0)
+            .on_action(move |action| gui::Message::Creator(Message::Edit(lines, action)))
+            .height(Length::Fixed(120.));
+
+        let mut col = Column::new().spacing(10);
+        col = col.push(widget::text(title.to_string()));
+        col = col.push(editor);
+
+        col.into()
+    }
+
+    /// Size and position as the launcher stores them, half filled pairs are dropped.
+    pub fn window_geometry(&self) -> WindowGeometry {
+        WindowGeometry {
+            size: WindowGeometry::parse_size(&format!(
+                "{}x{}",
+                self.app_window_width, self.app_window_height
+            )),
+            position: WindowGeometry::parse_position(&format!(
+                "{},{}",
+                self.app_window_x, self.app_window_y
+            )),
+            maximized: self.app_maximized,
+        }
+    }
+
+    pub fn set_window_geometry(&mut self, geometry: &WindowGeometry) {
+        let (width, height) = match geometry.size {
+            Some((width, height)) => (width.to_string(), height.to_string()),
+            None => (String::new(), String::new()),
+        };
+        let (x, y) = match geometry.position {
+            Some((x, y)) => (x.to_string(), y.to_string()),
+            None => (String::new(), String::new()),
+        };
+
+        self.app_window_width = width;
+        self.app_window_height = height;
+        self.app_window_x = x;
+        self.app_window_y = y;
+        self.app_maximized = geometry.maximized;
+    }
+
+    fn geometry_view(&self, options: &[EngineOption]) -> Element<gui::Message> {
+        let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+        row = row.push(widget::text("Window size"));
+
+        for (field, placeholder, value) in [
+            (Geometry::Width, "Width", &self.app_window_width),
+            (Geometry::Height, "Height", &self.app_window_height),
+            (Geometry::X, "X", &self.app_window_x),
+            (Geometry::Y, "Y", &self.app_window_y),
+        ] {
+            let input = TextInput::new(placeholder, value)
+                .on_input(move |s| gui::Message::Creator(Message::Geometry(field, s)))
+                .width(Length::Fill);
+
+            row = row.push(input);
+        }
+
+        if options.contains(&EngineOption::Maximized) {
+            let maximized = toggler(
+                String::from(EngineOption::Maximized.label()),
+                self.app_maximized,
+                |b| gui::Message::Creator(Message::Clicked(Buttons::Maximized(b))),
+            )
+            .width(Length::Shrink);
+
+            row = row.push(maximized);
+        }
+
+        row.into()
+    }
+
+    /// Proxies, download settings, permissions and clearing data apply to the
+    /// whole browser profile, so they cannot be used with the shared one.
+    fn needs_isolation(&self) -> bool {
+        self.proxy().needs_isolation()
+            || !self.app_download_dir.trim().is_empty()
+            || self.app_download_ask
+            || !self.app_permissions.is_empty()
+            || self.app_retention != Retention::Keep
+    }
+
+    /// Offers the retention modes of the selected browser, falling back to
+    /// keeping everything when it lacks the chosen one.
+    pub fn update_retention_modes(&mut self) {
+        self.retention_modes = self
+            .app_browser
+            ._type
+            .engine()
+            .map(|engine| engine.retention_modes())
+            .unwrap_or_default();
+        self.retention_labels = self
+            .retention_modes
+            .iter()
+            .map(|retention| retention.label().to_string())
+            .collect();
+
+        if !self.retention_modes.contains(&self.app_retention) {
+            self.app_retention = Retention::Keep;
+        }
+    }
+
+    pub fn proxy(&self) -> Proxy {
+        Proxy {
+            mode: self.app_proxy_mode,
+            host: self.app_proxy_host.trim().to_string(),
+            port: self.app_proxy_port.parse().ok(),
+            bypass: self
+                .app_proxy_bypass
+                .split(',')
+                .map(|host| host.trim().to_string())
+                .filter(|host| !host.is_empty())
+                .collect(),
+            pac_url: self.app_proxy_pac.trim().to_string(),
+        }
+    }
+
+    pub fn set_proxy(&mut self, proxy: &Proxy) {
+        self.app_proxy_mode = proxy.mode;
+        self.app_proxy_host.clone_from(&proxy.host);
+        self.app_proxy_port = proxy.port.map(|port| port.to_string()).unwrap_or_default();
+        self.app_proxy_bypass = proxy.bypass.join(", ");
+        self.app_proxy_pac.clone_from(&proxy.pac_url);
+    }
+
+    fn proxy_view(&self) -> Element<gui::Message> {
+        let selected = ProxyMode::ALL
+            .iter()
+            .position(|mode| *mode == self.app_proxy_mode);
+        let mode = dropdown(&self.proxy_modes, selected, |idx| {
+            gui::Message::Creator(Message::ProxyMode(idx))
+        })
+        .width(Length::Fixed(200.));
+
+        let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+        row = row.push(mode);
+
+        if self.app_proxy_mode.is_manual() {
+            let host = TextInput::new("Host", &self.app_proxy_host)
+                .on_input(|s| gui::Message::Creator(Message::Proxy(ProxyField::Host, s)))
+                .width(Length::Fill);
+            let port = TextInput::new("Port", &self.app_proxy_port)
+                .on_input(|s| gui::Message::Creator(Message::Proxy(ProxyField::Port, s)))
+                .width(Length::Fixed(100.));
+            let bypass = TextInput::new(
+                "No proxy for, e.g. localhost, .example.com",
+                &self.app_proxy_bypass,
+            )
+            .on_input(|s| gui::Message::Creator(Message::Proxy(ProxyField::Bypass, s)))
+            .width(Length::Fill);
+
+            row = row.push(host);
+            row = row.push(port);
+            row = row.push(bypass);
+        }
+
+        if self.app_proxy_mode == ProxyMode::Pac {
+            let pac = TextInput::new("Script URL", &self.app_proxy_pac)
+                .on_input(|s| gui::Message::Creator(Message::Proxy(ProxyField::Pac, s)))
+                .width(Length::Fill);
+
+            row = row.push(pac);
+        }
+
+        row.into()
+    }
+
+    fn choices_mut(&mut self, choices: Choices) -> &mut Vec<String> {
+        match choices {
+            Choices::Languages => &mut self.app_languages,
+            Choices::Dictionaries => &mut self.app_dictionaries,
+        }
+    }
+
+    fn choices_view(&self, choices: Choices) -> Element<gui::Message> {
+        let (title, values, installed) = match choices {
+            Choices::Languages => ("Languages", &self.app_languages, &self.installed_locales),
+            Choices::Dictionaries => (
+                "Spell checking",
+                &self.app_dictionaries,
+                &self.installed_dictionaries,
+            ),
+        };
+
+        let add = dropdown(installed, None, move |idx| {
+            gui::Message::Creator(Message::AddChoice(choices, idx))
+        })
+        .width(Length::Fixed(200.));
+
+        let mut col = Column::new().spacing(10);
+        col = col.push(widget::text(title));
+        col = col.push(add);
+
+        for (idx, value) in values.iter().enumerate() {
+            let remove = widget::button(icon_cache_get("edit-delete-symbolic", 16))
+                .on_press(gui::Message::Creator(Message::Clicked(
+                    Buttons::RemoveChoice(choices, idx),
+                )))
+                .padding(8)
+                .style(style::Button::Icon);
+
+            let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+            row = row.push(widget::text(value.clone()).width(Length::Fill));
+            row = row.push(remove);
+
+            col = col.push(row);
+        }
+
+        col.width(Length::Fill).into()
+    }
+
+    fn retention_view(&self) -> Element<gui::Message> {
+        let selected = self
+            .retention_modes
+            .iter()
+            .position(|retention| *retention == self.app_retention);
+        let retention = dropdown(&self.retention_labels, selected, |idx| {
+            gui::Message::Creator(Message::Retention(idx))
+        })
+        .width(Length::Fixed(250.));
+
+        let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+        row = row.push(widget::text(EngineOption::Retention.label()).width(Length::Fill));
+        row = row.push(retention);
+
+        row.into()
+    }
+
+    fn backend_view(&self) -> Element<gui::Message> {
+        let selected = DisplayBackend::ALL
+            .iter()
+            .position(|backend| *backend == self.app_backend);
+        let backend = dropdown(&self.backends, selected, |idx| {
+            gui::Message::Creator(Message::Backend(idx))
+        })
+        .width(Length::Fixed(250.));
+
+        let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+        row = row.push(widget::text(EngineOption::DisplayBackend.label()).width(Length::Fill));
+        row = row.push(backend);
+
+        row.into()
+    }
+
+    fn permissions_view(&self) -> Element<gui::Message> {
+        let mut col = Column::new().spacing(10);
+        col = col.push(widget::text("Permissions"));
+
+        for permission in Permission::ALL {
+            let selected = PermissionState::ALL
+                .iter()
+                .position(|state| *state == self.app_permissions.get(permission));
+            let state = dropdown(&self.permission_states, selected, move |idx| {
+                gui::Message::Creator(Message::Permission(permission, idx))
+            })
+            .width(Length::Fixed(150.));
+
+            let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+            row = row.push(widget::text(permission.label()).width(Length::Fill));
+            row = row.push(state);
+
+            col = col.push(row);
+        }
+
+        col.into()
+    }
+
+    fn downloads_view(&self) -> Element<gui::Message> {
+        let dir = TextInput::new(
+            "Download folder, e.g. ~/Work/Tickets, empty for the browser's",
+            &self.app_download_dir,
+        )
+        .on_input(|s| gui::Message::Creator(Message::DownloadDir(s)))
+        .width(Length::Fill);
+        let ask = toggler(String::from("Always ask"), self.app_download_ask, |b| {
+            gui::Message::Creator(Message::Clicked(Buttons::DownloadAsk(b)))
+        })
+        .width(Length::Shrink);
+
+        let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+        row = row.push(dir);
+        row = row.push(ask);
+
+        row.into()
+    }
+
+    fn user_agent_view(&self) -> Element<gui::Message> {
+        let presets = dropdown(
+            &self.user_agent_presets,
+            user_agent::preset(&self.app_user_agent),
+            |idx| gui::Message::Creator(Message::UserAgentPreset(idx)),
+        )
+        .width(Length::Fixed(200.));
+        let user_agent = TextInput::new(
+            "User agent, empty for the browser's own",
+            &self.app_user_agent,
+        )
+        .on_input(|s| gui::Message::Creator(Message::UserAgent(s)))
+        .width(Length::Fill);
+
+        let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+        row = row.push(user_agent);
+        row = row.push(presets);
+
+        row.into()
+    }
+
     pub fn view(&self) -> Element<gui::Message> {
         let app_title = TextInput::new("Title", &self.app_title)
             .id(self.app_title_id.clone())
@@ -249,32 +1111,70 @@ impl AppCreator {
         )
         .width(Length::Fixed(200.));
 
-        let navbar_toggle = toggler(String::from("Nav Bar"), self.app_navbar, |b| {
-            gui::Message::Creator(Message::Clicked(Buttons::Navbar(b)))
-        })
-        .width(Length::Fill);
+        let mut cat_row = Row::new().spacing(20).align_items(Alignment::Center);
+        cat_row = cat_row.push(category);
+
+        let options = match self.app_browser._type.engine() {
+            Some(engine) => engine.options(),
+            None => Vec::new(),
+        };
 
-        let browser_specific = match self.app_browser._type {
-            BrowserType::Firefox => navbar_toggle,
-            BrowserType::FirefoxFlatpak => navbar_toggle,
-            BrowserType::Librewolf => navbar_toggle,
-            BrowserType::WaterfoxFlatpak => navbar_toggle,
+        if options.contains(&EngineOption::WindowMode) {
+            let selected = WindowMode::ALL
+                .iter()
+                .position(|mode| *mode == self.app_window_mode);
+            let window_mode = dropdown(&self.window_modes, selected, |idx| {
+                gui::Message::Creator(Message::WindowMode(idx))
+            })
+            .width(Length::Fixed(200.));
 
-            _ => toggler(String::from("Isolated Profile"), self.app_isolated, |b| {
-                gui::Message::Creator(Message::Clicked(Buttons::IsolatedProfile(b)))
+            cat_row = cat_row.push(window_mode);
+        }
+
+        if options.contains(&EngineOption::Zoom) {
+            let selected = ZOOM_LEVELS.iter().position(|zoom| *zoom == self.app_zoom);
+            let zoom = dropdown(&self.zoom_levels, selected, |idx| {
+                gui::Message::Creator(Message::Zoom(idx))
             })
-            .width(Length::Fill),
-        };
+            .width(Length::Fixed(100.));
 
-        let incognito = toggler(String::from("Private Mode"), self.app_incognito, |b| {
-            gui::Message::Creator(Message::Clicked(Buttons::Incognito(b)))
-        })
-        .width(Length::Fill);
+            cat_row = cat_row.push(zoom);
+        }
 
-        let mut cat_row = Row::new().spacing(20).align_items(Alignment::Center);
-        cat_row = cat_row.push(category);
-        cat_row = cat_row.push(incognito);
-        cat_row = cat_row.push(browser_specific);
+        for option in &options {
+            let (value, button): (bool, fn(bool) -> Buttons) = match option {
+                EngineOption::PrivateWindow => (self.app_incognito, Buttons::Incognito),
+                EngineOption::IsolatedProfile => (self.app_isolated, Buttons::IsolatedProfile),
+                EngineOption::Navbar => (self.app_navbar, Buttons::Navbar),
+                EngineOption::ForceDark => (self.app_force_dark, Buttons::ForceDark),
+                EngineOption::FirefoxPreferences
+                | EngineOption::FirefoxExtensions
+                | EngineOption::WindowChrome
+                | EngineOption::ContentCss
+                | EngineOption::UserScript
+                | EngineOption::ChromiumPreferences
+                | EngineOption::ChromiumExtensions
+                | EngineOption::WindowMode
+                | EngineOption::WindowGeometry
+                | EngineOption::Maximized
+                | EngineOption::UserAgent
+                | EngineOption::Proxy
+                | EngineOption::Zoom
+                | EngineOption::Downloads
+                | EngineOption::Languages
+                | EngineOption::Permissions
+                | EngineOption::Retention
+                | EngineOption::Environment
+                | EngineOption::DisplayBackend => continue,
+            };
+
+            let option_toggle = toggler(String::from(option.label()), value, move |b| {
+                gui::Message::Creator(Message::Clicked(button(b)))
+            })
+            .width(Length::Fill);
+
+            cat_row = cat_row.push(option_toggle);
+        }
 
         let app_browsers = dropdown(&self.app_browsers, self.selected_browser, |idx| {
             gui::Message::Creator(Message::Browser(idx))
@@ -297,15 +1197,104 @@ impl AppCreator {
         browsers_row = browsers_row.push(app_done);
         browsers_row = browsers_row.push(creator_close);
 
-        let mut col = Column::new().spacing(20).padding(30);
+        let mut settings = Column::new().spacing(20);
 
         if self.warning.show {
-            col = col.push(self.warning.view());
+            settings = settings.push(self.warning.view());
+        }
+
+        settings = settings.push(row);
+        settings = settings.push(app_arguments);
+        settings = settings.push(cat_row);
+
+        if options.contains(&EngineOption::WindowGeometry) && self.app_window_mode.has_geometry() {
+            settings = settings.push(self.geometry_view(&options));
         }
 
-        col = col.push(row);
-        col = col.push(app_arguments);
-        col = col.push(cat_row);
+        if options.contains(&EngineOption::UserAgent) {
+            settings = settings.push(self.user_agent_view());
+        }
+
+        if options.contains(&EngineOption::Proxy) {
+            settings = settings.push(self.proxy_view());
+        }
+
+        if options.contains(&EngineOption::Downloads) {
+            settings = settings.push(self.downloads_view());
+        }
+
+        if options.contains(&EngineOption::Languages) {
+            let mut languages_row = Row::new().spacing(20);
+            languages_row = languages_row.push(self.choices_view(Choices::Languages));
+            languages_row = languages_row.push(self.choices_view(Choices::Dictionaries));
+
+            settings = settings.push(languages_row);
+        }
+
+        if options.contains(&EngineOption::Retention) {
+            settings = settings.push(self.retention_view());
+        }
+
+        if options.contains(&EngineOption::Permissions) {
+            settings = settings.push(self.permissions_view());
+        }
+
+        if options.contains(&EngineOption::DisplayBackend) {
+            settings = settings.push(self.backend_view());
+        }
+
+        if options.contains(&EngineOption::Environment) {
+            settings = settings.push(self.pairs_view(Pairs::Environment));
+        }
+
+        if options.contains(&EngineOption::WindowChrome) {
+            settings = settings.push(self.chrome_view());
+        }
+
+        if options.contains(&EngineOption::ContentCss) {
+            settings = settings.push(self.text_area_view(Lines::ContentCss, "Page CSS"));
+        }
+
+        if options.contains(&EngineOption::UserScript) {
+            settings = settings.push(self.text_area_view(Lines::UserScript, "User script"));
+        }
+
+        if options.contains(&EngineOption::FirefoxExtensions) {
+            let extensions = TextInput::new(
+                "Extensions from addons.mozilla.org, e.g. ublock-origin, bitwarden-password-manager",
+                &self.app_firefox_extensions,
+            )
+            .on_input(|s| gui::Message::Creator(Message::Extensions(s)))
+            .width(Length::Fill);
+
+            settings = settings.push(extensions);
+        }
+
+        if options.contains(&EngineOption::FirefoxPreferences) {
+            settings = settings.push(self.pairs_view(Pairs::FirefoxPrefs));
+        }
+
+        if options.contains(&EngineOption::ChromiumExtensions) {
+            let extensions = TextInput::new(
+                "Extensions separated by commas, unpacked directories or <id>=<path>.crx",
+                &self.app_chromium_extensions,
+            )
+            .on_input(|s| gui::Message::Creator(Message::ChromiumExtensions(s)))
+            .width(Length::Fill);
+
+            settings = settings.push(extensions);
+        }
+
+        if options.contains(&EngineOption::ChromiumPreferences) {
+            settings = settings.push(self.pairs_view(Pairs::ChromiumPrefs));
+        }
+
+        let settings = Scrollable::new(settings)
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        let mut col = Column::new().spacing(20).padding(30);
+        col = col.push(settings);
         col = col.push(browsers_row);
 
         col.into()
@@ -0,0 +1,397 @@
+use std::{
+    fs::{self, create_dir_all, File},
+    io::Write,
+    path::PathBuf,
+};
+
+use anyhow::Result;
+
+use crate::{common::WebAppLauncher, profiles::profile_root};
+
+use super::{
+    chromium_profile::{
+        install_crx_extensions, merge_json, preferences_overlay, remove_exceptions, seed_profile,
+        unpacked_extensions,
+    },
+    cookie_domain, download_dir,
+    environment::DisplayBackend,
+    exec_arg, has_flag, ice_profile, last_url,
+    permissions::{Permission, PermissionState},
+    quote_arg, site_host, site_origin, Engine, EngineOption, Retention, WindowGeometry, WindowMode,
+};
+
+/// Files of the content extension.
+const CONTENT_CSS: &str = "content.css";
+const USER_SCRIPT: &str = "user.js";
+
+/// Chromium and the browsers built on it: Chrome, Brave, Vivaldi, Edge and others.
+pub struct Chromium;
+
+impl Chromium {
+    /// Unpacked extension carrying the web app's page CSS and user script.
+    fn content_extension(&self, launcher: &WebAppLauncher) -> PathBuf {
+        profile_root(&launcher.web_browser, "extensions").join(&launcher.codename)
+    }
+
+    fn has_content(&self, launcher: &WebAppLauncher) -> bool {
+        !launcher.content_css.trim().is_empty() || !launcher.user_script.trim().is_empty()
+    }
+
+    /// Directories passed to `--load-extension`.
+    fn loaded_extensions(&self, launcher: &WebAppLauncher) -> Vec<PathBuf> {
+        let mut extensions = Vec::new();
+        let content_extension = self.content_extension(launcher);
+
+        if content_extension.join("manifest.json").exists() {
+            extensions.push(content_extension);
+        }
+
+        extensions.extend(unpacked_extensions(&launcher.chromium_extensions));
+
+        extensions
+    }
+
+    /// Download settings, which win over the same keys in the app's preferences.
+    fn download_preferences(&self, launcher: &WebAppLauncher) -> serde_json::Value {
+        let mut preferences = serde_json::json!({});
+
+        if let Some(dir) = download_dir(launcher) {
+            preferences = serde_json::json!({
+                "download": {
+                    "default_directory": dir,
+                    "directory_upgrade": true,
+                },
+                "savefile": {
+                    "default_directory": dir,
+                },
+            });
+        }
+
+        if launcher.download_ask || download_dir(launcher).is_some() {
+            preferences["download"]["prompt_for_download"] = launcher.download_ask.into();
+        }
+
+        preferences
+    }
+
+    /// Session only cookies, except for the app's site and its subdomains.
+    fn cookie_preferences(&self, launcher: &WebAppLauncher) -> serde_json::Value {
+        // Preferences outlive the launcher, so allowing them is written as well
+        let default = if launcher.retention == Retention::ClearCookies {
+            4
+        } else {
+            1
+        };
+
+        let mut preferences = serde_json::json!({
+            "profile": {
+                "default_content_setting_values": {
+                    "cookies": default,
+                },
+            },
+        });
+
+        if let (Retention::ClearCookies, Some(host)) = (launcher.retention, cookie_domain(launcher))
+        {
+            let pattern = format!("[*.]{},*", host);
+            preferences["profile"]["content_settings"]["exceptions"]["cookies"][pattern] =
+                serde_json::json!({ "setting": 1 });
+        }
+
+        preferences
+    }
+
+    fn language_preferences(&self, launcher: &WebAppLauncher) -> serde_json::Value {
+        let mut preferences = serde_json::json!({});
+
+        if !launcher.languages.is_empty() {
+            preferences["intl"]["accept_languages"] = launcher.languages.join(",").into();
+        }
+
+        if !launcher.dictionaries.is_empty() {
+            preferences["browser"]["enable_spellchecking"] = true.into();
+            preferences["spellcheck"]["dictionaries"] = launcher.dictionaries.clone().into();
+        }
+
+        preferences
+    }
+
+    fn create_content_extension(&self, launcher: &WebAppLauncher) -> Result<()> {
+        let extension_dir = self.content_extension(launcher);
+
+        // rebuilt from scratch so that cleared CSS or scripts do not linger
+        let _ = fs::remove_dir_all(&extension_dir);
+
+        let host = match site_host(launcher) {
+            Some(host) if self.has_content(launcher) => host,
+            _ => return Ok(()),
+        };
+
+        create_dir_all(&extension_dir)?;
+
+        let mut content_script = serde_json::json!({
+            "matches": [format!("*://{}/*", host)],
+            "run_at": "document_idle",
+        });
+
+        if !launcher.content_css.trim().is_empty() {
+            fs::write(extension_dir.join(CONTENT_CSS), &launcher.content_css)?;
+            content_script["css"] = serde_json::json!([CONTENT_CSS]);
+        }
+
+        if !launcher.user_script.trim().is_empty() {
+            fs::write(extension_dir.join(USER_SCRIPT), &launcher.user_script)?;
+            content_script["js"] = serde_json::json!([USER_SCRIPT]);
+        }
+
+        let manifest = serde_json::json!({
+            "manifest_version": 3,
+            "name": format!("{} customizations", launcher.name),
+            "version": "1.0",
+            "content_scripts": [content_script],
+        });
+
+        let mut file = File::create(extension_dir.join("manifest.json"))?;
+        file.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+impl Engine for Chromium {
+    fn options(&self) -> Vec<EngineOption> {
+        vec![
+            EngineOption::PrivateWindow,
+            EngineOption::IsolatedProfile,
+            EngineOption::ContentCss,
+            EngineOption::UserScript,
+            EngineOption::ChromiumPreferences,
+            EngineOption::ChromiumExtensions,
+            EngineOption::WindowMode,
+            EngineOption::WindowGeometry,
+            EngineOption::Maximized,
+            EngineOption::UserAgent,
+            EngineOption::Proxy,
+            EngineOption::Zoom,
+            EngineOption::ForceDark,
+            EngineOption::Downloads,
+            EngineOption::Languages,
+            EngineOption::Permissions,
+            EngineOption::Retention,
+            EngineOption::Environment,
+            EngineOption::DisplayBackend,
+        ]
+    }
+
+    fn profile_path(&self, launcher: &WebAppLauncher) -> Option<PathBuf> {
+        if launcher.isolate_profile {
+            Some(profile_root(&launcher.web_browser, "profiles").join(&launcher.codename))
+        } else {
+            None
+        }
+    }
+
+    fn legacy_profiles(&self, launcher: &WebAppLauncher) -> Vec<PathBuf> {
+        vec![ice_profile(&launcher.codename)]
+    }
+
+    fn create_profile(&self, launcher: &WebAppLauncher) -> Result<()> {
+        self.create_content_extension(launcher)?;
+
+        // a shared profile belongs to the user's browser
+        if let Some(user_data_dir) = self.profile_path(launcher) {
+            let mut preferences = preferences_overlay(&launcher.chromium_prefs);
+            merge_json(&mut preferences, &self.download_preferences(launcher));
+            merge_json(&mut preferences, &self.language_preferences(launcher));
+            merge_json(&mut preferences, &self.cookie_preferences(launcher));
+
+            let pattern = site_origin(launcher)
+                .map(|(scheme, host, port)| format!("{}://{}:{},*", scheme, host, port));
+
+            if let Some(pattern) = &pattern {
+                merge_json(
+                    &mut preferences,
+                    &launcher.permissions.chromium_exceptions(pattern),
+                );
+            }
+
+            seed_profile(&user_data_dir, &preferences, &serde_json::json!({}))?;
+
+            if let Some(pattern) = &pattern {
+                remove_exceptions(
+                    &user_data_dir,
+                    pattern,
+                    &launcher.permissions.chromium_cleared(),
+                )?;
+            }
+            install_crx_extensions(&user_data_dir, &launcher.chromium_extensions)?;
+        }
+
+        Ok(())
+    }
+
+    // the extension is the only copy of the page CSS and user script
+    fn content_dir(&self, _launcher: &WebAppLauncher) -> Option<PathBuf> {
+        None
+    }
+
+    fn content_files(&self, launcher: &WebAppLauncher) -> Option<(PathBuf, PathBuf)> {
+        let extension_dir = self.content_extension(launcher);
+        Some((
+            extension_dir.join(CONTENT_CSS),
+            extension_dir.join(USER_SCRIPT),
+        ))
+    }
+
+    fn remove_profile(&self, launcher: &WebAppLauncher) {
+        let mut paths = vec![self.content_extension(launcher)];
+        paths.extend(self.profile_path(launcher));
+
+        for path in paths {
+            if fs::remove_dir_all(path).is_ok() {
+                tracing::info!("Removed profile directory.");
+            }
+        }
+    }
+
+    fn cache_dirs(&self, launcher: &WebAppLauncher) -> Vec<PathBuf> {
+        match self.profile_path(launcher) {
+            Some(user_data_dir) if launcher.retention.clears_cache() => vec![
+                user_data_dir.join("Default/Cache"),
+                user_data_dir.join("Default/Code Cache"),
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    fn private_flag(&self, launcher: &WebAppLauncher) -> &'static str {
+        if launcher.web_browser.name.starts_with("Microsoft Edge") {
+            "--inprivate"
+        } else {
+            "--incognito"
+        }
+    }
+
+    fn exec_string(&self, launcher: &WebAppLauncher) -> String {
+        // a normal window gets the URL as the last argument, like a link
+        let target = match launcher.window_mode {
+            WindowMode::Normal => String::from("--new-window"),
+            _ => format!("--app={}", launcher.url),
+        };
+
+        let mut exec_string = format!(
+            "{} {} --class={} --name={} ",
+            launcher.exec,
+            target,
+            self.wm_class(launcher),
+            self.wm_class(launcher)
+        );
+
+        match launcher.backend {
+            DisplayBackend::Auto => {}
+            backend => exec_string.push_str(&format!("--ozone-platform={} ", backend.as_str())),
+        }
+
+        match launcher.window_mode {
+            WindowMode::Kiosk => exec_string.push_str("--kiosk "),
+            WindowMode::Fullscreen => exec_string.push_str("--start-fullscreen "),
+            WindowMode::App | WindowMode::Normal => {}
+        }
+
+        if launcher.window_mode.has_geometry() {
+            let geometry = &launcher.window_geometry;
+
+            // the size stays the one the window returns to from maximized
+            if let Some((width, height)) = geometry.size {
+                exec_string.push_str(&format!("--window-size={},{} ", width, height));
+            }
+
+            if let Some((x, y)) = geometry.position {
+                exec_string.push_str(&format!("--window-position={},{} ", x, y));
+            }
+
+            if geometry.maximized {
+                exec_string.push_str("--start-maximized ");
+            }
+        }
+
+        if let Some(profile_dir) = self.profile_path(launcher) {
+            let profile_path = profile_dir.to_str().unwrap();
+            exec_string.push_str(&format!("--user-data-dir={} ", profile_path));
+
+            // a running shared browser would ignore the proxy switches
+            for arg in launcher.proxy.chromium_args() {
+                exec_string.push_str(&format!("{} ", arg));
+            }
+        }
+
+        let extensions = self.loaded_extensions(launcher);
+
+        if !extensions.is_empty() {
+            let extension_paths: Vec<&str> = extensions
+                .iter()
+                .map(|path| path.to_str().unwrap())
+                .collect();
+            exec_string.push_str(&format!("--load-extension={} ", extension_paths.join(",")));
+        }
+
+        if launcher.zoom != 100 {
+            exec_string.push_str(&format!(
+                "--force-device-scale-factor={} ",
+                launcher.zoom as f32 / 100.0
+            ));
+        }
+
+        if launcher.force_dark {
+            // the first darkens the browser, the feature the pages
+            exec_string.push_str("--force-dark-mode --enable-features=WebContentsForceDark ");
+        }
+
+        if launcher.permissions.get(Permission::Autoplay) == PermissionState::Allow {
+            exec_string.push_str("--autoplay-policy=no-user-gesture-required ");
+        }
+
+        if let Some(language) = launcher.languages.first() {
+            exec_string.push_str(&format!("--lang={} ", language));
+        }
+
+        if !launcher.user_agent.is_empty() {
+            let user_agent = format!("--user-agent={}", launcher.user_agent);
+            exec_string.push_str(&format!("{} ", quote_arg(&user_agent)));
+        }
+
+        if launcher.is_incognito {
+            exec_string.push_str(&format!("{} ", self.private_flag(launcher)));
+        }
+
+        if !launcher.custom_parameters.is_empty() {
+            exec_string.push_str(&format!("{} ", launcher.custom_parameters));
+        }
+
+        if launcher.window_mode == WindowMode::Normal {
+            exec_string.push_str(&launcher.url);
+        }
+
+        exec_string
+    }
+
+    fn parse_launcher(&self, launcher: &mut WebAppLauncher) {
+        if launcher.url.is_empty() {
+            if let Some(url) =
+                exec_arg(&launcher.exec, "--app").or_else(|| last_url(&launcher.exec))
+            {
+                launcher.url = url;
+            }
+        }
+
+        if has_flag(&launcher.exec, "--kiosk") {
+            launcher.window_mode = WindowMode::Kiosk;
+        } else if has_flag(&launcher.exec, "--start-fullscreen") {
+            launcher.window_mode = WindowMode::Fullscreen;
+        }
+
+        launcher.isolate_profile |= exec_arg(&launcher.exec, "--user-data-dir").is_some();
+        launcher.is_incognito |=
+            has_flag(&launcher.exec, "--incognito") || has_flag(&launcher.exec, "--inprivate");
+    }
+}
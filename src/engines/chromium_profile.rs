@@ -0,0 +1,267 @@
+use std::{
+    fs::{self, create_dir_all, File},
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use zip::ZipArchive;
+
+/// Preferences offered in the creator, with the value a new row starts with.
+pub const KNOWN_PREFERENCES: [(&str, &str); 10] = [
+    ("download.default_directory", "\"/home/user/Downloads\""),
+    ("download.prompt_for_download", "false"),
+    ("partition.default_zoom_level.x", "0.0"),
+    ("spellcheck.dictionaries", "[\"en-US\"]"),
+    ("spellcheck.use_spelling_service", "false"),
+    ("credentials_enable_service", "false"),
+    ("autofill.profile_enabled", "false"),
+    ("profile.default_content_setting_values.notifications", "2"),
+    ("translate.enabled", "false"),
+    ("browser.show_home_button", "false"),
+];
+
+/// Seeded into new isolated profiles, the user's values win.
+fn baseline_preferences() -> Value {
+    json!({
+        "browser": {
+            "has_seen_welcome_page": true,
+            "check_default_browser": false,
+        },
+        "credentials_enable_service": false,
+        "profile": {
+            "password_manager_enabled": false,
+        },
+        "sync_promo": {
+            "show_on_first_run_allowed": false,
+        },
+    })
+}
+
+fn baseline_local_state() -> Value {
+    json!({
+        "background_mode": {
+            "enabled": false,
+        },
+        "browser": {
+            "has_seen_welcome_page": true,
+        },
+    })
+}
+
+/// Copies `overlay` into `base`, descending into objects so keys Chromium
+/// added itself stay untouched.
+pub fn merge_json(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// Turns `("download.prompt_for_download", "false")` pairs into nested JSON,
+/// values which are not valid JSON are taken as strings.
+pub fn preferences_overlay(pairs: &[(String, String)]) -> Value {
+    let mut overlay = json!({});
+
+    for (key, value) in pairs {
+        let key = key.trim();
+
+        if key.is_empty() {
+            continue;
+        }
+
+        let value = serde_json::from_str(value.trim())
+            .unwrap_or_else(|_| Value::String(value.trim().to_string()));
+
+        let mut nested = value;
+        for part in key.rsplit('.') {
+            nested = json!({ part: nested });
+        }
+
+        merge_json(&mut overlay, &nested);
+    }
+
+    overlay
+}
+
+/// Merges `overlay` into the JSON file at `path`, creating it when missing.
+pub fn seed_json(path: &Path, overlay: &Value) -> Result<()> {
+    let mut content = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|_| json!({})),
+        Err(_) => json!({}),
+    };
+
+    merge_json(&mut content, overlay);
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let mut file = File::create(path)?;
+    file.write_all(serde_json::to_string(&content)?.as_bytes())?;
+
+    Ok(())
+}
+
+/// Prepares `Default/Preferences` and `Local State` of an isolated profile
+/// before Chromium first starts with it.
+pub fn seed_profile(user_data_dir: &Path, preferences: &Value, local_state: &Value) -> Result<()> {
+    let preferences_path = user_data_dir.join("Default/Preferences");
+    let local_state_path = user_data_dir.join("Local State");
+
+    // the baseline is for new profiles, later edits keep what the user changed since
+    let mut app_preferences = if preferences_path.exists() {
+        json!({})
+    } else {
+        baseline_preferences()
+    };
+    merge_json(&mut app_preferences, preferences);
+
+    let mut app_local_state = if local_state_path.exists() {
+        json!({})
+    } else {
+        baseline_local_state()
+    };
+    merge_json(&mut app_local_state, local_state);
+
+    seed_json(&preferences_path, &app_preferences)?;
+    seed_json(&local_state_path, &app_local_state)?;
+
+    // skips the first run UI
+    File::create(user_data_dir.join("First Run"))?;
+
+    Ok(())
+}
+
+/// Removes the site exceptions of `content_types` for `pattern` from
+/// `Default/Preferences`, merging only ever adds them.
+pub fn remove_exceptions(
+    user_data_dir: &Path,
+    pattern: &str,
+    content_types: &[&str],
+) -> Result<()> {
+    let path = user_data_dir.join("Default/Preferences");
+
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(());
+    };
+
+    let mut preferences: Value = serde_json::from_str(&content)?;
+    let Some(exceptions) = preferences.pointer_mut("/profile/content_settings/exceptions") else {
+        return Ok(());
+    };
+
+    let mut removed = false;
+    for content_type in content_types {
+        if let Some(sites) = exceptions
+            .get_mut(*content_type)
+            .and_then(Value::as_object_mut)
+        {
+            removed |= sites.remove(pattern).is_some();
+        }
+    }
+
+    if removed {
+        fs::write(&path, serde_json::to_string(&preferences)?)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the version of a packed extension, read from the manifest of the
+/// zip archive which follows the CRX3 header.
+pub fn crx_version(path: &Path) -> Result<String> {
+    let data = fs::read(path)?;
+
+    let header_len = match data.get(0..12) {
+        Some([b'C', b'r', b'2', b'4', 3, 0, 0, 0, len @ ..]) => {
+            u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize
+        }
+        _ => return Err(anyhow!("not a CRX3 file")),
+    };
+
+    let zip = data
+        .get(12 + header_len..)
+        .ok_or_else(|| anyhow!("truncated header"))?;
+    let mut archive = ZipArchive::new(Cursor::new(zip))?;
+
+    let manifest: Value = serde_json::from_reader(archive.by_name("manifest.json")?)?;
+    let version = manifest["version"]
+        .as_str()
+        .ok_or_else(|| anyhow!("extension without version"))?;
+
+    Ok(version.to_string())
+}
+
+/// Chromium extension ids are 32 letters from a to p.
+fn is_extension_id(id: &str) -> bool {
+    id.len() == 32 && id.bytes().all(|c| (b'a'..=b'p').contains(&c))
+}
+
+fn is_crx(extension: &str) -> bool {
+    extension.to_lowercase().ends_with(".crx")
+}
+
+/// Unpacked extension directories, for `--load-extension`.
+pub fn unpacked_extensions(extensions: &[String]) -> Vec<PathBuf> {
+    extensions
+        .iter()
+        .filter(|extension| !is_crx(extension))
+        .map(PathBuf::from)
+        .filter(|path| path.join("manifest.json").exists())
+        .collect()
+}
+
+/// Registers packed extensions, written as `<id>=<path to .crx>`, as external
+/// extensions of the profile, Chromium installs them on its next start.
+pub fn install_crx_extensions(user_data_dir: &Path, extensions: &[String]) -> Result<()> {
+    let external_dir = user_data_dir.join("External Extensions");
+
+    // the directory only holds our entries, the profile is not shared
+    let _ = fs::remove_dir_all(&external_dir);
+
+    for extension in extensions.iter().filter(|extension| is_crx(extension)) {
+        let (id, crx) = match extension.split_once('=') {
+            Some((id, crx)) if is_extension_id(id.trim()) => (id.trim(), Path::new(crx.trim())),
+            _ => {
+                tracing::error!(
+                    "Extension {} needs its id, as in <id>={}",
+                    extension,
+                    extension
+                );
+                continue;
+            }
+        };
+
+        match crx_version(crx) {
+            Ok(version) => {
+                create_dir_all(&external_dir)?;
+
+                // the browser may be sandboxed and miss the original location
+                let profile_crx = external_dir.join(format!("{}.crx", id));
+                fs::copy(crx, &profile_crx)?;
+
+                let entry = json!({
+                    "external_crx": profile_crx,
+                    "external_version": version,
+                });
+
+                let mut file = File::create(external_dir.join(format!("{}.json", id)))?;
+                file.write_all(serde_json::to_string_pretty(&entry)?.as_bytes())?;
+            }
+            Err(e) => tracing::error!("Cannot read extension {}: {}", extension, e),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use super::quote_arg;
+
+/// Variables offered in the creator, with an example value.
+pub const KNOWN_VARIABLES: [(&str, &str); 6] = [
+    ("MOZ_ENABLE_WAYLAND", "1"),
+    ("GDK_SCALE", "2"),
+    ("QT_SCALE_FACTOR", "1.5"),
+    ("TZ", "UTC"),
+    ("LANG", "en_US.UTF-8"),
+    ("LC_TIME", "en_GB.UTF-8"),
+];
+
+/// Display server the browser talks to, browsers pick one on their own by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayBackend {
+    #[default]
+    Auto,
+    Wayland,
+    /// X11, through XWayland on a Wayland session.
+    X11,
+}
+
+impl DisplayBackend {
+    pub const ALL: [DisplayBackend; 3] = [
+        DisplayBackend::Auto,
+        DisplayBackend::Wayland,
+        DisplayBackend::X11,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DisplayBackend::Auto => "Automatic",
+            DisplayBackend::Wayland => "Wayland",
+            DisplayBackend::X11 => "X11",
+        }
+    }
+
+    /// Value of the `X-WebApp-Backend` key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisplayBackend::Auto => "auto",
+            DisplayBackend::Wayland => "wayland",
+            DisplayBackend::X11 => "x11",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        DisplayBackend::ALL
+            .into_iter()
+            .find(|backend| backend.as_str() == value)
+            .unwrap_or_default()
+    }
+
+    /// `GDK_BACKEND` of GTK browsers.
+    pub fn gdk_backend(&self) -> Option<&'static str> {
+        match self {
+            DisplayBackend::Auto => None,
+            DisplayBackend::Wayland => Some("wayland"),
+            DisplayBackend::X11 => Some("x11"),
+        }
+    }
+
+    /// `QT_QPA_PLATFORM` of Qt browsers.
+    pub fn qt_platform(&self) -> Option<&'static str> {
+        match self {
+            DisplayBackend::Auto => None,
+            DisplayBackend::Wayland => Some("wayland"),
+            DisplayBackend::X11 => Some("xcb"),
+        }
+    }
+}
+
+/// Shell style variable names, anything else `env` would take for the command.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `env NAME=value …` in front of the command of the `Exec` key, later
+/// variables win over earlier ones of the same name.
+pub fn env_prefix(variables: &[(String, String)]) -> String {
+    let assignments: Vec<String> = variables
+        .iter()
+        .filter(|(name, _)| {
+            // rows just added in the creator have no name yet
+            let valid = is_valid_name(name);
+
+            if !valid && !name.is_empty() {
+                tracing::warn!("Skipped environment variable with invalid name {:?}.", name);
+            }
+
+            valid
+        })
+        .map(|(name, value)| quote_arg(&format!("{}={}", name, value)))
+        .collect();
+
+    if assignments.is_empty() {
+        String::new()
+    } else {
+        format!("env {} ", assignments.join(" "))
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::Write,
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use image::io::Reader as ImageReader;
+
+use crate::common::{home_dir, is_svg, BrowserType, WebAppLauncher};
+
+use super::{environment::DisplayBackend, last_url, Engine, EngineOption};
+
+const APP_ID_PREFIX: &str = "org.gnome.Epiphany.WebApp_";
+
+/// GNOME Web, started in its own web app mode.
+pub struct Epiphany;
+
+impl Epiphany {
+    /// Epiphany names the profile, the desktop file and the window after the app id.
+    fn app_id(&self, launcher: &WebAppLauncher) -> String {
+        let id: String = launcher
+            .codename
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect();
+
+        format!("{}{}", APP_ID_PREFIX, id)
+    }
+
+    fn profile_root(&self, launcher: &WebAppLauncher) -> PathBuf {
+        let mut profile_dir = home_dir();
+
+        match launcher.web_browser._type {
+            BrowserType::EpiphanyFlatpak => profile_dir.push(".var/app/org.gnome.Epiphany/data"),
+            _ => profile_dir.push(".local/share"),
+        };
+
+        profile_dir
+    }
+
+    fn create_app_icon(&self, launcher: &WebAppLauncher, path: PathBuf) -> Result<()> {
+        // Epiphany only reads png icons, vector icons stay in the desktop file
+        if is_svg(&launcher.icon) {
+            return Ok(());
+        }
+
+        ImageReader::open(&launcher.icon)?
+            .with_guessed_format()?
+            .decode()?
+            .save_with_format(path, image::ImageFormat::Png)?;
+
+        Ok(())
+    }
+
+    fn create_desktop_file(&self, launcher: &WebAppLauncher, path: PathBuf) -> Result<()> {
+        let mut output = File::create(path)?;
+
+        writeln!(output, "[Desktop Entry]")?;
+        writeln!(output, "Name={}", launcher.name)?;
+        writeln!(output, "Exec={}", self.exec_string(launcher))?;
+        writeln!(output, "Terminal=false")?;
+        writeln!(output, "Type=Application")?;
+        writeln!(output, "Icon={}", launcher.icon)?;
+        writeln!(output, "StartupWMClass={}", self.wm_class(launcher))?;
+        writeln!(output, "StartupNotify=true")?;
+        writeln!(output, "X-Purism-FormFactor=Workstation;Mobile;")?;
+
+        Ok(())
+    }
+}
+
+impl Engine for Epiphany {
+    fn options(&self) -> Vec<EngineOption> {
+        // web apps always get their own profile and never show the address bar
+        vec![EngineOption::Environment, EngineOption::DisplayBackend]
+    }
+
+    fn profile_path(&self, launcher: &WebAppLauncher) -> Option<PathBuf> {
+        Some(self.profile_root(launcher).join(self.app_id(launcher)))
+    }
+
+    fn create_profile(&self, launcher: &WebAppLauncher) -> Result<()> {
+        let app_id = self.app_id(launcher);
+        let profile_path = self.profile_root(launcher).join(&app_id);
+
+        create_dir_all(&profile_path)?;
+
+        // marks the profile as a web app
+        File::create(profile_path.join(".app"))?;
+
+        if let Err(e) = self.create_app_icon(launcher, profile_path.join("app-icon.png")) {
+            tracing::error!("Cannot create Epiphany app icon: {}", e);
+        }
+
+        self.create_desktop_file(launcher, profile_path.join(format!("{}.desktop", app_id)))?;
+
+        Ok(())
+    }
+
+    fn backend_env(&self, backend: DisplayBackend) -> Vec<(String, String)> {
+        backend
+            .gdk_backend()
+            .map(|gdk_backend| (String::from("GDK_BACKEND"), gdk_backend.to_string()))
+            .into_iter()
+            .collect()
+    }
+
+    fn private_flag(&self, _launcher: &WebAppLauncher) -> &'static str {
+        ""
+    }
+
+    fn exec_string(&self, launcher: &WebAppLauncher) -> String {
+        let profile_path = self.profile_root(launcher).join(self.app_id(launcher));
+        let profile_path = profile_path.to_str().unwrap();
+
+        let mut exec_string = format!(
+            "{} --application-mode --profile={} ",
+            launcher.exec, profile_path
+        );
+
+        if !launcher.custom_parameters.is_empty() {
+            exec_string.push_str(&format!("{} ", launcher.custom_parameters));
+        }
+
+        exec_string.push_str(&launcher.url);
+
+        exec_string
+    }
+
+    fn parse_launcher(&self, launcher: &mut WebAppLauncher) {
+        if launcher.url.is_empty() {
+            if let Some(url) = last_url(&launcher.exec) {
+                launcher.url = url;
+            }
+        }
+
+        launcher.isolate_profile = true;
+    }
+
+    fn wm_class(&self, launcher: &WebAppLauncher) -> String {
+        self.app_id(launcher)
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    fs::{self, create_dir_all, File},
+    io::Write,
+    path::PathBuf,
+};
+
+use anyhow::Result;
+
+use crate::{
+    common::{home_dir, WebAppLauncher},
+    profiles::InstallSource,
+};
+
+use super::{
+    cookie_domain, download_dir, environment::DisplayBackend, exec_arg, has_flag, ice_profile,
+    last_url, Engine, EngineOption, Retention, WindowMode,
+};
+
+/// KDE's QtWebEngine based browser.
+pub struct Falkon;
+
+impl Falkon {
+    /// Falkon looks profiles up by name in its own configuration directory.
+    fn profiles_root(&self, launcher: &WebAppLauncher) -> PathBuf {
+        let config_dir = match InstallSource::of(&launcher.web_browser) {
+            InstallSource::Native => home_dir().join(".config"),
+            InstallSource::Flatpak(app_id) => {
+                home_dir().join(".var/app").join(app_id).join("config")
+            }
+            InstallSource::Snap(name) => home_dir().join("snap").join(name).join("current/.config"),
+        };
+
+        config_dir.join("falkon/profiles")
+    }
+
+    fn profile_name(&self, launcher: &WebAppLauncher) -> String {
+        format!("webapp-{}", launcher.codename)
+    }
+
+    /// Keys of `settings.ini` the web app manages as `(section, key, value)`,
+    /// `None` removes the key. Everything else is Falkon's own.
+    fn settings(
+        &self,
+        launcher: &WebAppLauncher,
+    ) -> Vec<(&'static str, &'static str, Option<String>)> {
+        let navbar = launcher.window_mode.navbar(launcher.navbar);
+        let clears_cache = launcher.retention.clears_cache();
+        let clears_cookies = launcher.retention == Retention::ClearCookies;
+
+        let mut settings = vec![
+            (
+                "Browser-View-Settings",
+                "showNavigationToolbar",
+                Some(navbar.to_string()),
+            ),
+            (
+                "Browser-View-Settings",
+                "showBookmarksToolbar",
+                Some(String::from("false")),
+            ),
+            (
+                "Browser-View-Settings",
+                "showStatusBar",
+                Some(String::from("false")),
+            ),
+            (
+                "Browser-View-Settings",
+                "showMenubar",
+                Some(String::from("false")),
+            ),
+            ("Web-URL-Settings", "homepage", Some(launcher.url.clone())),
+            ("Web-URL-Settings", "afterLaunch", Some(String::from("0"))),
+            (
+                "Web-Browser-Settings",
+                "UserAgent",
+                Some(settings_string(&launcher.user_agent))
+                    .filter(|_| !launcher.user_agent.is_empty()),
+            ),
+            (
+                "Web-Browser-Settings",
+                "deleteCacheOnClose",
+                Some(clears_cache.to_string()),
+            ),
+            // whitelisted cookies survive the deletion on close
+            (
+                "Cookie-Settings",
+                "deleteCookiesOnClose",
+                Some(clears_cookies.to_string()),
+            ),
+        ];
+
+        if let Some(domain) = cookie_domain(launcher).filter(|_| clears_cookies) {
+            settings.push((
+                "Cookie-Settings",
+                "whitelist",
+                Some(settings_string(&domain)),
+            ));
+        }
+
+        let dir = download_dir(launcher);
+
+        // an empty path makes Falkon ask every time
+        let default_path = match &dir {
+            Some(dir) if !launcher.download_ask => Some(dir.display().to_string()),
+            _ if launcher.download_ask => Some(String::new()),
+            _ => None,
+        };
+        settings.push((
+            "DownloadManager",
+            "defaultDownloadPath",
+            default_path.map(|path| settings_string(&path)),
+        ));
+
+        if let Some(dir) = dir {
+            settings.push((
+                "DownloadManager",
+                "lastDownloadPath",
+                Some(settings_string(&dir.display().to_string())),
+            ));
+        }
+
+        settings
+    }
+}
+
+type Setting = (&'static str, &'static str, Option<String>);
+
+/// Appends the keys of `section` which are not written yet.
+fn push_missing(merged: &mut String, section: &str, settings: &[Setting], written: &mut [bool]) {
+    for (idx, (_, key, value)) in settings.iter().enumerate() {
+        if settings[idx].0 != section || written[idx] {
+            continue;
+        }
+
+        if let Some(value) = value {
+            merged.push_str(&format!("{}={}\n", key, value));
+        }
+        written[idx] = true;
+    }
+}
+
+/// Sets `settings` in the QSettings ini `content`, keeping the other keys.
+fn merge_settings(content: &str, settings: &[Setting]) -> String {
+    let mut merged = String::new();
+    let mut written = vec![false; settings.len()];
+    let mut section = String::new();
+    // blank lines end a section, its missing keys go before them
+    let mut blank_lines = 0;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            push_missing(&mut merged, &section, settings, &mut written);
+            section = name.to_string();
+        }
+
+        merged.push_str(&"\n".repeat(blank_lines));
+        blank_lines = 0;
+
+        if let Some((key, _)) = trimmed.split_once('=') {
+            let managed = settings
+                .iter()
+                .position(|(s, k, _)| *s == section && *k == key.trim());
+
+            if let Some(idx) = managed {
+                if let Some(value) = &settings[idx].2 {
+                    merged.push_str(&format!("{}={}\n", settings[idx].1, value));
+                }
+                written[idx] = true;
+                continue;
+            }
+        }
+
+        merged.push_str(line);
+        merged.push('\n');
+    }
+
+    push_missing(&mut merged, &section, settings, &mut written);
+
+    // sections the file does not have yet
+    for idx in 0..settings.len() {
+        let section = settings[idx].0;
+
+        if written[idx] {
+            continue;
+        }
+
+        let mut entries = String::new();
+        push_missing(&mut entries, section, settings, &mut written);
+
+        if !entries.is_empty() {
+            if !merged.is_empty() {
+                merged.push('\n');
+            }
+            merged.push_str(&format!("[{}]\n{}", section, entries));
+        }
+    }
+
+    merged
+}
+
+/// Quotes a QSettings string, unquoted values with commas are read as lists.
+fn settings_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Engine for Falkon {
+    fn options(&self) -> Vec<EngineOption> {
+        vec![
+            EngineOption::PrivateWindow,
+            EngineOption::IsolatedProfile,
+            EngineOption::Navbar,
+            EngineOption::WindowMode,
+            EngineOption::WindowGeometry,
+            EngineOption::UserAgent,
+            EngineOption::Downloads,
+            EngineOption::Retention,
+            EngineOption::Environment,
+            EngineOption::DisplayBackend,
+        ]
+    }
+
+    fn profile_path(&self, launcher: &WebAppLauncher) -> Option<PathBuf> {
+        if launcher.isolate_profile {
+            Some(
+                self.profiles_root(launcher)
+                    .join(self.profile_name(launcher)),
+            )
+        } else {
+            None
+        }
+    }
+
+    fn legacy_profiles(&self, launcher: &WebAppLauncher) -> Vec<PathBuf> {
+        vec![ice_profile(&launcher.codename)]
+    }
+
+    fn create_profile(&self, launcher: &WebAppLauncher) -> Result<()> {
+        // the toolbars of the shared profile belong to the user
+        let Some(profile_path) = self.profile_path(launcher) else {
+            return Ok(());
+        };
+
+        create_dir_all(&profile_path)?;
+
+        // Falkon keeps its own settings and session options in the same file
+        let path = profile_path.join("settings.ini");
+        let content = fs::read_to_string(&path).unwrap_or_default();
+
+        let mut file = File::create(path)?;
+        file.write_all(merge_settings(&content, &self.settings(launcher)).as_bytes())?;
+
+        Ok(())
+    }
+
+    fn remove_profile(&self, launcher: &WebAppLauncher) {
+        let profile_path = self
+            .profiles_root(launcher)
+            .join(self.profile_name(launcher));
+
+        // portable profiles of launchers created by older versions
+        for path in [profile_path, ice_profile(&launcher.codename)] {
+            if std::fs::remove_dir_all(path).is_ok() {
+                tracing::info!("Removed profile directory.");
+            }
+        }
+    }
+
+    fn backend_env(&self, backend: DisplayBackend) -> Vec<(String, String)> {
+        backend
+            .qt_platform()
+            .map(|platform| (String::from("QT_QPA_PLATFORM"), platform.to_string()))
+            .into_iter()
+            .collect()
+    }
+
+    fn private_flag(&self, _launcher: &WebAppLauncher) -> &'static str {
+        "--private-browsing"
+    }
+
+    fn exec_string(&self, launcher: &WebAppLauncher) -> String {
+        let mut exec_string = format!("{} --wmclass {} ", launcher.exec, self.wm_class(launcher));
+
+        if launcher.isolate_profile {
+            exec_string.push_str(&format!(
+                "--profile={} --no-remote ",
+                self.profile_name(launcher)
+            ));
+        } else {
+            exec_string.push_str("--new-window ");
+        }
+
+        // Falkon has no kiosk mode, fullscreen comes closest
+        if matches!(
+            launcher.window_mode,
+            WindowMode::Kiosk | WindowMode::Fullscreen
+        ) {
+            exec_string.push_str("--fullscreen ");
+        }
+
+        // Qt applies it to the first window, there is no option to start maximized
+        if let Some(geometry) = launcher
+            .window_geometry
+            .x11_geometry()
+            .filter(|_| launcher.window_mode.has_geometry())
+        {
+            exec_string.push_str(&format!("-qwindowgeometry {} ", geometry));
+        }
+
+        if launcher.is_incognito {
+            exec_string.push_str(&format!("{} ", self.private_flag(launcher)));
+        }
+
+        if !launcher.custom_parameters.is_empty() {
+            exec_string.push_str(&format!("{} ", launcher.custom_parameters));
+        }
+
+        exec_string.push_str(&launcher.url);
+
+        exec_string
+    }
+
+    fn parse_launcher(&self, launcher: &mut WebAppLauncher) {
+        if launcher.url.is_empty() {
+            if let Some(url) = last_url(&launcher.exec) {
+                launcher.url = url;
+            }
+        }
+
+        launcher.isolate_profile |= exec_arg(&launcher.exec, "--profile").is_some();
+        launcher.is_incognito |= has_flag(&launcher.exec, "--private-browsing");
+
+        if has_flag(&launcher.exec, "--fullscreen") && launcher.window_mode == WindowMode::App {
+            launcher.window_mode = WindowMode::Fullscreen;
+        }
+    }
+}
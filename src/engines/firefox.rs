@@ -0,0 +1,446 @@
+use std::{
+    fs::{self, create_dir_all, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::{
+    common::{home_dir, BrowserType, WebAppLauncher},
+    profiles::profile_root,
+};
+
+use super::{
+    cookie_domain, download_dir,
+    environment::DisplayBackend,
+    firefox_chrome::FirefoxChrome,
+    firefox_policies::webapp_prefs,
+    firefox_prefs::{clear_saved_prefs, PrefValue, Prefs},
+    has_flag, last_url,
+    permissions::STALE_PERMISSION_PREFS,
+    site_host, Engine, EngineOption, Retention, WindowMode,
+};
+
+const BROWSER_WINDOW: &str = "chrome://browser/content/browser.xhtml";
+
+const DEFAULT_PERMISSIONS: &str = "resource://app/defaults/permissions";
+
+/// Entries of Firefox's own defaults, which a file of the web app replaces.
+const BUILTIN_PERMISSIONS: &str = "\
+origin\tuitour\t1\thttps://www.mozilla.org
+origin\tuitour\t1\thttps://support.mozilla.org
+origin\tuitour\t1\tabout:home
+origin\tuitour\t1\tabout:newtab
+origin\tinstall\t1\thttps://addons.mozilla.org
+origin\tremote-troubleshooting\t1\thttps://support.mozilla.org
+";
+
+/// Default site permissions of the web app, read at every start.
+const PERMISSIONS_FILE: &str = "webapp-permissions";
+
+/// Firefox and its forks: Librewolf, Waterfox and others.
+pub struct Firefox;
+
+impl Firefox {
+    fn profile_root(&self, launcher: &WebAppLauncher) -> PathBuf {
+        let fork = match launcher.web_browser._type {
+            BrowserType::Librewolf => "librewolf",
+            BrowserType::WaterfoxFlatpak => "waterfox",
+            _ => "firefox",
+        };
+
+        profile_root(&launcher.web_browser, fork)
+    }
+
+    /// Before profiles followed the install type every fork used a flatpak path.
+    fn legacy_root(&self, launcher: &WebAppLauncher) -> PathBuf {
+        let mut profile_dir = home_dir();
+
+        match launcher.web_browser._type {
+            BrowserType::Librewolf => {
+                profile_dir.push(".var/app/io.gitlab.librewolf-community/data/ice/librewolf")
+            }
+            BrowserType::WaterfoxFlatpak => {
+                profile_dir.push(".var/app/net.waterfox.waterfox/data/ice/waterfox")
+            }
+            _ => profile_dir.push(".var/app/org.mozilla.firefox/data/ice/firefox"),
+        };
+
+        profile_dir
+    }
+
+    /// Clears data on shutdown, sparing cookies the app's site is allowed to keep.
+    fn retention_prefs(&self, launcher: &WebAppLauncher) -> Prefs {
+        let mut prefs = Prefs::new();
+        let sanitize = launcher.retention.clears_cache();
+        let cookies = launcher.retention == Retention::ClearCookies;
+
+        prefs.set(
+            "privacy.sanitize.sanitizeOnShutdown",
+            PrefValue::Bool(sanitize),
+        );
+
+        if sanitize {
+            // history and logins stay, which Firefox clears by default
+            for (item, clear) in [
+                ("cache", true),
+                ("cookies", cookies),
+                ("offlineApps", cookies),
+                ("history", false),
+                ("formdata", false),
+                ("downloads", false),
+                ("sessions", false),
+                ("siteSettings", false),
+            ] {
+                prefs.set(
+                    &format!("privacy.clearOnShutdown.{}", item),
+                    PrefValue::Bool(clear),
+                );
+            }
+
+            // the same items as grouped by newer versions
+            for (item, clear) in [
+                ("cache", true),
+                ("cookiesAndStorage", cookies),
+                ("historyFormDataAndDownloads", false),
+                ("siteSettings", false),
+            ] {
+                prefs.set(
+                    &format!("privacy.clearOnShutdown_v2.{}", item),
+                    PrefValue::Bool(clear),
+                );
+            }
+        }
+
+        prefs
+    }
+
+    /// Site permissions of the web app, allowing the site's cookies exempts
+    /// them from clearing on shutdown.
+    fn default_permissions(&self, launcher: &WebAppLauncher) -> String {
+        let mut permissions = String::new();
+
+        if launcher.retention == Retention::ClearCookies {
+            if let Some(domain) = cookie_domain(launcher) {
+                permissions.push_str(&format!("host\tcookie\t1\t{}\n", domain));
+            }
+        }
+
+        if let Some(host) = site_host(launcher) {
+            permissions.push_str(&launcher.permissions.firefox_permissions(&host));
+        }
+
+        permissions
+    }
+
+    fn create_user_js(&self, launcher: &WebAppLauncher, path: PathBuf) -> Result<()> {
+        // the app's own preferences are merged last and win over the derived ones
+        let app_prefs = Prefs::for_app(&launcher.firefox_prefs);
+        let mut prefs = launcher.permissions.firefox_prefs();
+        prefs.merge(&launcher.proxy.firefox_prefs());
+        prefs.merge(&self.retention_prefs(launcher));
+
+        let defaults_url = match path.parent().map(|dir| dir.join(PERMISSIONS_FILE)) {
+            Some(file) if !self.default_permissions(launcher).is_empty() => {
+                Url::from_file_path(file).map(|url| url.to_string()).ok()
+            }
+            _ => None,
+        };
+        prefs.set(
+            "permissions.manager.defaultsUrl",
+            PrefValue::String(defaults_url.unwrap_or_else(|| DEFAULT_PERMISSIONS.to_string())),
+        );
+
+        if let Some(profile_path) = path.parent() {
+            clear_saved_prefs(&profile_path.join("prefs.js"), &STALE_PERMISSION_PREFS)?;
+        }
+
+        // an empty override is no override, and it replaces one left in prefs.js
+        prefs.set(
+            "general.useragent.override",
+            PrefValue::String(launcher.user_agent.clone()),
+        );
+
+        // prefs.js keeps the last zoom unless user.js sets the default
+        let pixels_per_px = if launcher.zoom != 100 {
+            (launcher.zoom as f32 / 100.0).to_string()
+        } else {
+            String::from("-1.0")
+        };
+        prefs.set(
+            "layout.css.devPixelsPerPx",
+            PrefValue::String(pixels_per_px),
+        );
+
+        // Firefox cannot darken light pages, but sites follow the dark color scheme
+        if launcher.force_dark {
+            prefs.set("ui.systemUsesDarkTheme", PrefValue::Int(1));
+            prefs.set(
+                "layout.css.prefers-color-scheme.content-override",
+                PrefValue::Int(0),
+            );
+        } else {
+            // pages follow the browser theme
+            prefs.set(
+                "layout.css.prefers-color-scheme.content-override",
+                PrefValue::Int(2),
+            );
+
+            // any value of it wins over the system theme, so it has to go
+            if app_prefs.get("ui.systemUsesDarkTheme").is_none() {
+                if let Some(profile_path) = path.parent() {
+                    clear_saved_prefs(&profile_path.join("prefs.js"), &["ui.systemUsesDarkTheme"])?;
+                }
+            }
+        }
+
+        if let Some(dir) = download_dir(launcher) {
+            prefs.set(
+                "browser.download.dir",
+                PrefValue::String(dir.display().to_string()),
+            );
+            prefs.set("browser.download.folderList", PrefValue::Int(2));
+        }
+
+        if launcher.download_ask || download_dir(launcher).is_some() {
+            prefs.set(
+                "browser.download.useDownloadDir",
+                PrefValue::Bool(!launcher.download_ask),
+            );
+            prefs.set(
+                "browser.download.always_ask_before_handling_new_types",
+                PrefValue::Bool(launcher.download_ask),
+            );
+        }
+
+        if !launcher.languages.is_empty() {
+            prefs.set(
+                "intl.accept_languages",
+                PrefValue::String(launcher.languages.join(",")),
+            );
+            // only takes effect with the language pack installed
+            prefs.set(
+                "intl.locale.requested",
+                PrefValue::String(launcher.languages[0].clone()),
+            );
+        }
+
+        if !launcher.dictionaries.is_empty() {
+            prefs.set(
+                "spellchecker.dictionary",
+                PrefValue::String(launcher.dictionaries.join(",")),
+            );
+        }
+
+        prefs.merge(&app_prefs);
+        prefs.merge(&webapp_prefs());
+
+        let mut file = File::create(path)?;
+        file.write_all(prefs.to_user_js().as_bytes())?;
+
+        Ok(())
+    }
+
+    fn create_user_content_css(&self, launcher: &WebAppLauncher, path: PathBuf) -> Result<()> {
+        let mut file = File::create(path)?;
+
+        if let (Some(host), false) = (site_host(launcher), launcher.content_css.trim().is_empty()) {
+            writeln!(file, "@-moz-document domain({}) {{", host)?;
+            for line in launcher.content_css.lines() {
+                writeln!(file, "    {}", line)?;
+            }
+            writeln!(file, "}}")?;
+        }
+
+        Ok(())
+    }
+
+    fn create_user_chrome_css(&self, launcher: &WebAppLauncher, path: PathBuf) -> Result<()> {
+        let css = match launcher.window_mode {
+            // a normal window keeps the whole toolbox, only the colors still apply
+            WindowMode::Normal => {
+                let chrome = FirefoxChrome {
+                    show_tabs: true,
+                    mini_toolbar: false,
+                    readonly_url: false,
+                    ..launcher.firefox_chrome.clone()
+                };
+                chrome.user_chrome_css(true)
+            }
+            mode => launcher
+                .firefox_chrome
+                .user_chrome_css(mode.navbar(launcher.navbar)),
+        };
+
+        let mut file = File::create(path)?;
+        file.write_all(css.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Firefox restores the size and place of its window from `xulstore.json`,
+    /// there are no command line flags to start maximized or fullscreen.
+    fn update_xulstore(&self, launcher: &WebAppLauncher, path: PathBuf) -> Result<()> {
+        let mut xulstore: Value = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .filter(Value::is_object)
+            .unwrap_or_else(|| json!({}));
+
+        let window = &mut xulstore[BROWSER_WINDOW]["main-window"];
+        let geometry = &launcher.window_geometry;
+        let has_geometry = launcher.window_mode.has_geometry();
+
+        if has_geometry {
+            if let Some((width, height)) = geometry.size {
+                window["width"] = json!(width.to_string());
+                window["height"] = json!(height.to_string());
+            }
+
+            if let Some((x, y)) = geometry.position {
+                window["screenX"] = json!(x.to_string());
+                window["screenY"] = json!(y.to_string());
+            }
+        }
+
+        let sizemode = match launcher.window_mode {
+            WindowMode::Fullscreen => "fullscreen",
+            _ if has_geometry && geometry.maximized => "maximized",
+            _ if has_geometry && (geometry.size.is_some() || geometry.position.is_some()) => {
+                "normal"
+            }
+            // any other size the user left the window in stays
+            _ if window["sizemode"] == "fullscreen" => "normal",
+            _ => return Ok(()),
+        };
+
+        window["sizemode"] = json!(sizemode);
+
+        let mut file = File::create(path)?;
+        file.write_all(serde_json::to_string(&xulstore)?.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+impl Engine for Firefox {
+    fn options(&self) -> Vec<EngineOption> {
+        vec![
+            EngineOption::PrivateWindow,
+            EngineOption::Navbar,
+            EngineOption::FirefoxPreferences,
+            EngineOption::FirefoxExtensions,
+            EngineOption::WindowChrome,
+            EngineOption::ContentCss,
+            EngineOption::WindowMode,
+            EngineOption::WindowGeometry,
+            EngineOption::Maximized,
+            EngineOption::UserAgent,
+            EngineOption::Proxy,
+            EngineOption::Zoom,
+            EngineOption::ForceDark,
+            EngineOption::Downloads,
+            EngineOption::Languages,
+            EngineOption::Permissions,
+            EngineOption::Retention,
+            EngineOption::Environment,
+            EngineOption::DisplayBackend,
+        ]
+    }
+
+    fn profile_path(&self, launcher: &WebAppLauncher) -> Option<PathBuf> {
+        Some(self.profile_root(launcher).join(&launcher.codename))
+    }
+
+    fn legacy_profiles(&self, launcher: &WebAppLauncher) -> Vec<PathBuf> {
+        vec![self.legacy_root(launcher).join(&launcher.codename)]
+    }
+
+    fn create_profile(&self, launcher: &WebAppLauncher) -> Result<()> {
+        let profile_path = self.profile_root(launcher).join(&launcher.codename);
+        let chrome_dir = profile_path.join("chrome");
+
+        create_dir_all(&chrome_dir)?;
+
+        self.create_user_js(launcher, profile_path.join("user.js"))?;
+
+        fs::write(
+            profile_path.join(PERMISSIONS_FILE),
+            format!(
+                "{}{}",
+                BUILTIN_PERMISSIONS,
+                self.default_permissions(launcher)
+            ),
+        )?;
+        self.create_user_chrome_css(launcher, chrome_dir.join("userChrome.css"))?;
+
+        self.create_user_content_css(launcher, chrome_dir.join("userContent.css"))?;
+        self.update_xulstore(launcher, profile_path.join("xulstore.json"))?;
+
+        Ok(())
+    }
+
+    fn backend_env(&self, backend: DisplayBackend) -> Vec<(String, String)> {
+        let variables: &[(&str, &str)] = match backend {
+            DisplayBackend::Auto => &[],
+            DisplayBackend::Wayland => &[("MOZ_ENABLE_WAYLAND", "1")],
+            // a session wide MOZ_ENABLE_WAYLAND=1 would win over GDK_BACKEND
+            DisplayBackend::X11 => &[("MOZ_ENABLE_WAYLAND", "0"), ("GDK_BACKEND", "x11")],
+        };
+
+        variables
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn private_flag(&self, _launcher: &WebAppLauncher) -> &'static str {
+        "--private-window"
+    }
+
+    fn exec_string(&self, launcher: &WebAppLauncher) -> String {
+        let profile_path = self.profile_root(launcher).join(&launcher.codename);
+        let profile_path = profile_path.to_str().unwrap();
+
+        let mut exec_string = format!(
+            "{} --class {} --name {} --profile {} --no-remote ",
+            launcher.exec,
+            self.wm_class(launcher),
+            self.wm_class(launcher),
+            profile_path
+        );
+
+        if launcher.window_mode == WindowMode::Kiosk {
+            exec_string.push_str("--kiosk ");
+        }
+
+        if launcher.is_incognito {
+            exec_string.push_str(&format!("{} ", self.private_flag(launcher)));
+        }
+
+        if !launcher.custom_parameters.is_empty() {
+            exec_string.push_str(&format!("{} ", launcher.custom_parameters));
+        }
+
+        exec_string.push_str(&launcher.url);
+
+        exec_string
+    }
+
+    fn parse_launcher(&self, launcher: &mut WebAppLauncher) {
+        if launcher.url.is_empty() {
+            if let Some(url) = last_url(&launcher.exec) {
+                launcher.url = url;
+            }
+        }
+
+        launcher.is_incognito |= has_flag(&launcher.exec, "--private-window");
+
+        if has_flag(&launcher.exec, "--kiosk") {
+            launcher.window_mode = WindowMode::Kiosk;
+        }
+    }
+}
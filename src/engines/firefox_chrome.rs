@@ -0,0 +1,94 @@
+/// Parts of the Firefox window a web app keeps, composed into `userChrome.css`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FirefoxChrome {
+    pub show_tabs: bool,
+    pub mini_toolbar: bool,
+    pub readonly_url: bool,
+    pub title_color: String,
+    pub custom_css: Vec<String>,
+}
+
+const HIDE_TABS: &str = "#TabsToolbar, #tabbrowser-tabs {
+    visibility: collapse !important;
+}
+";
+
+const HIDE_NAVBAR: &str = "#nav-bar {
+    visibility: collapse !important;
+}
+";
+
+// everything but the navigation buttons and the address bar
+const MINI_TOOLBAR: &str = "#nav-bar-customization-target > :not(#back-button, #forward-button, #reload-button, #stop-reload-button, #urlbar-container),
+#PanelUI-button, #nav-bar-overflow-button, #unified-extensions-button {
+    display: none !important;
+}
+";
+
+const HIDE_URL: &str = "#urlbar-container {
+    display: none !important;
+}
+";
+
+const READONLY_URL: &str = "#urlbar {
+    pointer-events: none !important;
+}
+
+#urlbar-input-container > :not(.urlbar-input-box) {
+    display: none !important;
+}
+";
+
+impl FirefoxChrome {
+    pub fn user_chrome_css(&self, navbar: bool) -> String {
+        let mut css = String::new();
+
+        if !self.show_tabs {
+            css.push_str(HIDE_TABS);
+        }
+
+        // the full nav bar leaves the toolbar as the user made it
+        if !navbar {
+            if self.mini_toolbar || self.readonly_url {
+                css.push_str(MINI_TOOLBAR);
+
+                if !self.mini_toolbar {
+                    css.push_str("#back-button, #forward-button, #reload-button, #stop-reload-button {\n    display: none !important;\n}\n");
+                }
+
+                if self.readonly_url {
+                    css.push_str(READONLY_URL);
+                } else {
+                    css.push_str(HIDE_URL);
+                }
+            } else {
+                css.push_str(HIDE_NAVBAR);
+            }
+        }
+
+        if is_css_color(&self.title_color) {
+            css.push_str(&format!(
+                "#navigator-toolbox, #titlebar, #TabsToolbar, #nav-bar {{\n    background-color: {} !important;\n}}\n",
+                self.title_color
+            ));
+        }
+
+        for line in &self.custom_css {
+            css.push_str(line);
+            css.push('\n');
+        }
+
+        css
+    }
+}
+
+/// Hex colors (`#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`) and color names,
+/// the only values pasted into userChrome.css.
+pub fn is_css_color(value: &str) -> bool {
+    match value.strip_prefix('#') {
+        Some(hex) => {
+            matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => !value.is_empty() && value.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    fs::{self, create_dir_all, File},
+    io::{copy, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use reqwest::blocking::Client;
+
+use super::firefox_prefs::{PrefValue, Prefs};
+
+/// Settings every Firefox web app gets, written after the app's own preferences.
+///
+/// Firefox only reads `policies.json` from its installation, which is read
+/// only for flatpak and distribution packages, so these are plain user.js
+/// preferences: set again at every start, but not locked.
+pub const WEBAPP_PREFS: [(&str, &str); 11] = [
+    ("datareporting.healthreport.uploadEnabled", "false"),
+    ("datareporting.policy.dataSubmissionEnabled", "false"),
+    ("toolkit.telemetry.enabled", "false"),
+    ("toolkit.telemetry.unified", "false"),
+    ("app.shield.optoutstudies.enabled", "false"),
+    ("browser.aboutwelcome.enabled", "false"),
+    ("browser.startup.homepage_override.mstone", "\"ignore\""),
+    ("startup.homepage_welcome_url", "\"\""),
+    ("trailhead.firstrun.didSeeAboutWelcome", "true"),
+    // sideloaded extensions are enabled without asking
+    ("extensions.autoDisableScopes", "0"),
+    ("extensions.enabledScopes", "15"),
+];
+
+const AMO_API: &str = "https://addons.mozilla.org/api/v5/addons/addon";
+
+/// Extensions installed by us, so removed entries can be uninstalled.
+const INSTALLED_LIST: &str = "webapps-extensions.list";
+
+pub fn webapp_prefs() -> Prefs {
+    let mut prefs = Prefs::new();
+
+    for (key, value) in WEBAPP_PREFS {
+        prefs.set(key, PrefValue::parse(value));
+    }
+
+    prefs
+}
+
+/// Accepts the name of an add-on on addons.mozilla.org or its page URL.
+fn addon_slug(entry: &str) -> Option<String> {
+    let entry = entry.trim().trim_end_matches('/');
+
+    if entry.is_empty() {
+        return None;
+    }
+
+    match entry.split_once("/addon/") {
+        Some((_, slug)) => slug.split('/').next().map(|s| s.to_string()),
+        None => Some(entry.to_string()),
+    }
+}
+
+/// Returns the extension id and the download URL of the latest version.
+fn resolve(client: &Client, slug: &str) -> Result<(String, String)> {
+    let response = client
+        .get(format!("{}/{}/", AMO_API, slug))
+        .send()?
+        .error_for_status()?
+        .text()?;
+    let addon: serde_json::Value = serde_json::from_str(&response)?;
+
+    let guid = addon["guid"]
+        .as_str()
+        .ok_or_else(|| anyhow!("{} has no extension id", slug))?;
+    let url = addon["current_version"]["file"]["url"]
+        .as_str()
+        .ok_or_else(|| anyhow!("{} has no downloadable version", slug))?;
+
+    Ok((guid.to_string(), url.to_string()))
+}
+
+fn download(client: &Client, url: &str, path: &Path) -> Result<()> {
+    let mut response = client.get(url).send()?.error_for_status()?;
+
+    // never leave a half written extension behind
+    let part = path.with_extension("part");
+    let mut file = File::create(&part)?;
+    copy(&mut response, &mut file)?;
+    fs::rename(part, path)?;
+
+    Ok(())
+}
+
+fn sync_extensions(extensions_dir: &Path, extensions: &[String]) -> Result<()> {
+    create_dir_all(extensions_dir)?;
+
+    let list_path = extensions_dir.join(INSTALLED_LIST);
+
+    // lines of `<name> <extension id>`
+    let previous: Vec<(String, String)> = fs::read_to_string(&list_path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(slug, guid)| (slug.to_string(), guid.to_string()))
+        .collect();
+
+    let client = Client::new();
+    let mut installed: Vec<(String, String)> = Vec::new();
+    let mut failed: Vec<String> = Vec::new();
+
+    for slug in extensions.iter().filter_map(|entry| addon_slug(entry)) {
+        match resolve(&client, &slug) {
+            Ok((guid, url)) => {
+                let xpi = extensions_dir.join(format!("{}.xpi", guid));
+
+                if !xpi.exists() {
+                    match download(&client, &url, &xpi) {
+                        Ok(_) => tracing::info!("Installed extension {}.", slug),
+                        Err(e) => {
+                            tracing::error!("Cannot download extension {}: {}", slug, e);
+                            failed.push(slug.clone());
+                        }
+                    }
+                }
+
+                installed.push((slug, guid));
+            }
+            Err(e) => {
+                tracing::error!("Cannot find extension {}: {}", slug, e);
+                failed.push(slug.clone());
+
+                // keep what is installed while offline
+                if let Some(entry) = previous.iter().find(|(s, _)| *s == slug) {
+                    installed.push(entry.clone());
+                }
+            }
+        }
+    }
+
+    for (_, guid) in &previous {
+        if !installed.iter().any(|(_, g)| g == guid) {
+            let _ = fs::remove_file(extensions_dir.join(format!("{}.xpi", guid)));
+        }
+    }
+
+    let mut list = File::create(list_path)?;
+    for (slug, guid) in installed {
+        writeln!(list, "{} {}", slug, guid)?;
+    }
+
+    if !failed.is_empty() {
+        return Err(anyhow!("cannot install {}", failed.join(", ")));
+    }
+
+    Ok(())
+}
+
+/// Sideloads the extensions into the profile, Firefox picks them up on its
+/// next start.
+pub async fn install_extensions(profile_path: PathBuf, extensions: Vec<String>) -> Result<()> {
+    let extensions_dir = profile_path.join("extensions");
+
+    if extensions.is_empty() && !extensions_dir.join(INSTALLED_LIST).exists() {
+        return Ok(());
+    }
+
+    // the blocking client has to stay off the async runtime
+    tokio::task::spawn_blocking(move || sync_extensions(&extensions_dir, &extensions)).await?
+}
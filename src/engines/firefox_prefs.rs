@@ -0,0 +1,202 @@
+use std::{
+    fs::{self, create_dir_all, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use crate::common::config_dir;
+
+/// Preferences offered in the creator, with the value a new row starts with.
+pub const KNOWN_PREFS: [(&str, &str); 12] = [
+    ("browser.cache.disk.enable", "true"),
+    ("browser.contentblocking.category", "\"standard\""),
+    ("browser.startup.homepage", "\"about:home\""),
+    ("browser.tabs.warnOnClose", "false"),
+    ("dom.webnotifications.enabled", "true"),
+    ("general.smoothScroll", "true"),
+    ("layout.css.devPixelsPerPx", "\"-1.0\""),
+    ("media.autoplay.default", "0"),
+    ("network.cookie.cookieBehavior", "0"),
+    ("privacy.resistFingerprinting", "false"),
+    ("signon.rememberSignons", "true"),
+    ("ui.systemUsesDarkTheme", "1"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrefValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+
+impl PrefValue {
+    /// Reads a JavaScript literal, anything unquoted which is neither a
+    /// boolean nor an integer is taken as a string.
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+
+        if let Ok(b) = value.parse::<bool>() {
+            return PrefValue::Bool(b);
+        }
+
+        if let Ok(i) = value.parse::<i64>() {
+            return PrefValue::Int(i);
+        }
+
+        let unquoted = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .map(|v| v.replace("\\\"", "\"").replace("\\\\", "\\"));
+
+        PrefValue::String(unquoted.unwrap_or_else(|| value.to_string()))
+    }
+
+    pub fn to_js(&self) -> String {
+        match self {
+            PrefValue::Bool(b) => b.to_string(),
+            PrefValue::Int(i) => i.to_string(),
+            PrefValue::String(s) => {
+                format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+        }
+    }
+}
+
+/// Ordered `user.js` preferences, later values replace earlier ones.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Prefs {
+    prefs: Vec<(String, PrefValue)>,
+}
+
+impl Prefs {
+    pub fn new() -> Self {
+        Prefs { prefs: Vec::new() }
+    }
+
+    pub fn set(&mut self, key: &str, value: PrefValue) {
+        match self.prefs.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.prefs.push((key.to_string(), value)),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&PrefValue> {
+        self.prefs.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn merge(&mut self, other: &Prefs) {
+        for (key, value) in &other.prefs {
+            self.set(key, value.clone());
+        }
+    }
+
+    /// Reads the `user_pref("key", value);` lines of a `user.js` file.
+    pub fn parse(content: &str) -> Self {
+        let mut prefs = Prefs::new();
+
+        for line in content.lines() {
+            let pref = line
+                .trim()
+                .strip_prefix("user_pref(")
+                .and_then(|l| l.strip_suffix(");"))
+                .and_then(|l| l.split_once(','));
+
+            if let Some((key, value)) = pref {
+                let key = key.trim().trim_matches('"');
+                prefs.set(key, PrefValue::parse(value));
+            }
+        }
+
+        prefs
+    }
+
+    pub fn from_pairs(pairs: &[(String, String)]) -> Self {
+        let mut prefs = Prefs::new();
+
+        for (key, value) in pairs {
+            if !key.trim().is_empty() {
+                prefs.set(key.trim(), PrefValue::parse(value));
+            }
+        }
+
+        prefs
+    }
+
+    pub fn to_pairs(&self) -> Vec<(String, String)> {
+        self.prefs
+            .iter()
+            .map(|(key, value)| (key.clone(), value.to_js()))
+            .collect()
+    }
+
+    pub fn to_user_js(&self) -> String {
+        let mut user_js = String::new();
+
+        for (key, value) in &self.prefs {
+            user_js.push_str(&format!("user_pref(\"{}\", {});\n", key, value.to_js()));
+        }
+
+        user_js
+    }
+
+    pub fn baseline() -> Self {
+        Prefs::parse(include_str!("../../data/runtime/firefox/profile/user.js"))
+    }
+
+    fn global_path() -> PathBuf {
+        config_dir().join("firefox-prefs.js")
+    }
+
+    /// Overrides of the baseline the user made in the settings.
+    pub fn global() -> Self {
+        match std::fs::read_to_string(Prefs::global_path()) {
+            Ok(content) => Prefs::parse(&content),
+            Err(_) => Prefs::new(),
+        }
+    }
+
+    pub fn save_global(&self) -> Result<()> {
+        create_dir_all(config_dir())?;
+
+        let mut file = File::create(Prefs::global_path())?;
+        file.write_all(self.to_user_js().as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Baseline, global overrides and the web app's own preferences.
+    pub fn for_app(app_prefs: &[(String, String)]) -> Self {
+        let mut prefs = Prefs::baseline();
+        prefs.merge(&Prefs::global());
+        prefs.merge(&Prefs::from_pairs(app_prefs));
+
+        prefs
+    }
+}
+
+/// Removes `keys` from the `prefs.js` of a profile. user.js can only set
+/// preferences, one it no longer sets keeps its last value there.
+pub fn clear_saved_prefs(prefs_js: &Path, keys: &[&str]) -> Result<()> {
+    let Ok(content) = fs::read_to_string(prefs_js) else {
+        return Ok(());
+    };
+
+    let prefixes: Vec<String> = keys
+        .iter()
+        .map(|key| format!("user_pref(\"{}\",", key))
+        .collect();
+
+    let kept: String = content
+        .lines()
+        .filter(|line| !prefixes.iter().any(|prefix| line.starts_with(prefix)))
+        .map(|line| format!("{}\n", line))
+        .collect();
+
+    if kept != content {
+        fs::write(prefs_js, kept)?;
+    }
+
+    Ok(())
+}
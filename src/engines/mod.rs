@@ -0,0 +1,443 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use url::Url;
+
+use crate::common::{home_dir, BrowserType, WebAppLauncher};
+
+use self::environment::DisplayBackend;
+
+mod chromium;
+pub mod chromium_profile;
+pub mod environment;
+mod epiphany;
+mod falkon;
+mod firefox;
+pub mod firefox_chrome;
+pub mod firefox_policies;
+pub mod firefox_prefs;
+pub mod permissions;
+pub mod proxy;
+mod qutebrowser;
+pub mod user_agent;
+
+pub use chromium::Chromium;
+pub use epiphany::Epiphany;
+pub use falkon::Falkon;
+pub use firefox::Firefox;
+pub use qutebrowser::Qutebrowser;
+
+/// Page CSS of the web app, kept in its content directory.
+pub const CONTENT_CSS_FILE: &str = "webapp-content.css";
+/// User script of the web app, next to its page CSS.
+pub const USER_SCRIPT_FILE: &str = "webapp-script.js";
+
+/// Per-app settings an engine knows how to apply, shown as toggles in the creator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineOption {
+    PrivateWindow,
+    IsolatedProfile,
+    Navbar,
+    FirefoxPreferences,
+    FirefoxExtensions,
+    WindowChrome,
+    ContentCss,
+    UserScript,
+    ChromiumPreferences,
+    ChromiumExtensions,
+    WindowMode,
+    WindowGeometry,
+    Maximized,
+    UserAgent,
+    Proxy,
+    Zoom,
+    ForceDark,
+    Downloads,
+    Languages,
+    Permissions,
+    Retention,
+    Environment,
+    DisplayBackend,
+}
+
+impl EngineOption {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EngineOption::PrivateWindow => "Private Mode",
+            EngineOption::IsolatedProfile => "Isolated Profile",
+            EngineOption::Navbar => "Nav Bar",
+            EngineOption::FirefoxPreferences | EngineOption::ChromiumPreferences => "Preferences",
+            EngineOption::FirefoxExtensions | EngineOption::ChromiumExtensions => "Extensions",
+            EngineOption::WindowChrome => "Window",
+            EngineOption::ContentCss => "Page CSS",
+            EngineOption::UserScript => "User script",
+            EngineOption::WindowMode => "Window mode",
+            EngineOption::WindowGeometry => "Window size",
+            EngineOption::Maximized => "Maximized",
+            EngineOption::UserAgent => "User agent",
+            EngineOption::Proxy => "Proxy",
+            EngineOption::Zoom => "Zoom",
+            EngineOption::ForceDark => "Dark mode",
+            EngineOption::Downloads => "Downloads",
+            EngineOption::Languages => "Languages",
+            EngineOption::Permissions => "Permissions",
+            EngineOption::Retention => "Browsing data",
+            EngineOption::Environment => "Environment",
+            EngineOption::DisplayBackend => "Display server",
+        }
+    }
+}
+
+/// Zoom levels offered in the creator, in percent.
+pub const ZOOM_LEVELS: [u32; 11] = [50, 67, 75, 80, 90, 100, 110, 125, 150, 175, 200];
+
+/// How the web app's window opens, each engine translates it to its own flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindowMode {
+    /// A single page window without browser controls.
+    #[default]
+    App,
+    /// Fullscreen without any way out but closing the app, for dashboards.
+    Kiosk,
+    /// An app window which starts fullscreen.
+    Fullscreen,
+    /// A regular browser window with tabs, still using the app's profile.
+    Normal,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 4] = [
+        WindowMode::App,
+        WindowMode::Kiosk,
+        WindowMode::Fullscreen,
+        WindowMode::Normal,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WindowMode::App => "App window",
+            WindowMode::Kiosk => "Kiosk",
+            WindowMode::Fullscreen => "Fullscreen",
+            WindowMode::Normal => "Normal window",
+        }
+    }
+
+    /// Whether the navigation bar shows, given the launcher's own setting.
+    pub fn navbar(&self, navbar: bool) -> bool {
+        match self {
+            WindowMode::Normal => true,
+            WindowMode::Kiosk => false,
+            WindowMode::App | WindowMode::Fullscreen => navbar,
+        }
+    }
+
+    /// Fullscreen windows have no size or position of their own.
+    pub fn has_geometry(&self) -> bool {
+        matches!(self, WindowMode::App | WindowMode::Normal)
+    }
+
+    /// Value of the `X-WebApp-WindowMode` key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WindowMode::App => "app",
+            WindowMode::Kiosk => "kiosk",
+            WindowMode::Fullscreen => "fullscreen",
+            WindowMode::Normal => "normal",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        WindowMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == value)
+            .unwrap_or_default()
+    }
+}
+
+/// What the web app's profile keeps between launches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Retention {
+    #[default]
+    Keep,
+    ClearCache,
+    /// Clears the cache too, only the cookies of the app's own site stay.
+    ClearCookies,
+    /// Every launch starts from the profile as it was created.
+    Ephemeral,
+}
+
+impl Retention {
+    pub const ALL: [Retention; 4] = [
+        Retention::Keep,
+        Retention::ClearCache,
+        Retention::ClearCookies,
+        Retention::Ephemeral,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Retention::Keep => "Keep all data",
+            Retention::ClearCache => "Clear cache on exit",
+            Retention::ClearCookies => "Keep only the app's cookies",
+            Retention::Ephemeral => "Fresh profile on every launch",
+        }
+    }
+
+    /// Value of the `X-WebApp-Retention` key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Retention::Keep => "keep",
+            Retention::ClearCache => "cache",
+            Retention::ClearCookies => "cookies",
+            Retention::Ephemeral => "ephemeral",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        Retention::ALL
+            .into_iter()
+            .find(|retention| retention.as_str() == value)
+            .unwrap_or_default()
+    }
+
+    pub fn clears_cache(&self) -> bool {
+        matches!(self, Retention::ClearCache | Retention::ClearCookies)
+    }
+}
+
+pub trait Engine {
+    /// Options the creator offers for this engine.
+    fn options(&self) -> Vec<EngineOption>;
+
+    /// Profile directory of the web app, `None` when the browser profile is shared.
+    fn profile_path(&self, launcher: &WebAppLauncher) -> Option<PathBuf>;
+
+    /// Profiles of this web app in locations used by older versions.
+    fn legacy_profiles(&self, _launcher: &WebAppLauncher) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Prepares the profile directory before the launcher is written.
+    fn create_profile(&self, launcher: &WebAppLauncher) -> Result<()>;
+
+    /// Directory keeping the page CSS and user script, which are too long for
+    /// the desktop file.
+    fn content_dir(&self, launcher: &WebAppLauncher) -> Option<PathBuf> {
+        self.profile_path(launcher)
+    }
+
+    /// Page CSS and user script files the engine writes itself, read back
+    /// when there is no `content_dir`.
+    fn content_files(&self, _launcher: &WebAppLauncher) -> Option<(PathBuf, PathBuf)> {
+        None
+    }
+
+    fn remove_profile(&self, launcher: &WebAppLauncher) {
+        if let Some(profile_path) = self.profile_path(launcher) {
+            if std::fs::remove_dir_all(profile_path).is_ok() {
+                tracing::info!("Removed profile directory.");
+            }
+        }
+    }
+
+    /// Data retention modes the creator offers for this engine.
+    fn retention_modes(&self) -> Vec<Retention> {
+        Retention::ALL.to_vec()
+    }
+
+    /// Cache directories the browser cannot clear on exit by itself, they are
+    /// removed before the next launch instead.
+    fn cache_dirs(&self, _launcher: &WebAppLauncher) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Variables which select `backend`, for browsers without a command line flag.
+    fn backend_env(&self, _backend: DisplayBackend) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    fn private_flag(&self, launcher: &WebAppLauncher) -> &'static str;
+
+    /// Command line of the launcher's `Exec` key.
+    fn exec_string(&self, launcher: &WebAppLauncher) -> String;
+
+    /// Recovers settings from the `Exec` line of launchers that miss our keys.
+    fn parse_launcher(&self, launcher: &mut WebAppLauncher);
+
+    fn wm_class(&self, launcher: &WebAppLauncher) -> String {
+        format!("WebApp-{}", launcher.codename)
+    }
+}
+
+impl BrowserType {
+    pub fn engine(&self) -> Option<&'static dyn Engine> {
+        match self {
+            BrowserType::NoBrowser => None,
+            BrowserType::Firefox
+            | BrowserType::FirefoxFlatpak
+            | BrowserType::Librewolf
+            | BrowserType::WaterfoxFlatpak => Some(&Firefox),
+            BrowserType::Chromium => Some(&Chromium),
+            BrowserType::Falkon => Some(&Falkon),
+            BrowserType::Epiphany | BrowserType::EpiphanyFlatpak => Some(&Epiphany),
+            BrowserType::Qutebrowser => Some(&Qutebrowser),
+        }
+    }
+}
+
+/// Size and place of the web app's window, anything unset is up to the browser.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub size: Option<(u32, u32)>,
+    pub position: Option<(i32, i32)>,
+    pub maximized: bool,
+}
+
+impl WindowGeometry {
+    /// Reads `800x600`, the value of the `X-WebApp-WindowSize` key.
+    pub fn parse_size(value: &str) -> Option<(u32, u32)> {
+        let (width, height) = value.split_once(['x', ','])?;
+        let width = width.trim().parse().ok().filter(|w| *w > 0)?;
+        let height = height.trim().parse().ok().filter(|h| *h > 0)?;
+
+        Some((width, height))
+    }
+
+    /// Reads `100,50`, the value of the `X-WebApp-WindowPosition` key.
+    pub fn parse_position(value: &str) -> Option<(i32, i32)> {
+        let (x, y) = value.split_once(',')?;
+
+        Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+    }
+
+    pub fn size_string(&self) -> String {
+        match self.size {
+            Some((width, height)) => format!("{}x{}", width, height),
+            None => String::new(),
+        }
+    }
+
+    pub fn position_string(&self) -> String {
+        match self.position {
+            Some((x, y)) => format!("{},{}", x, y),
+            None => String::new(),
+        }
+    }
+
+    /// X11 style `800x600+100+50` geometry, as Qt's `-qwindowgeometry` takes it.
+    pub fn x11_geometry(&self) -> Option<String> {
+        let mut geometry = self.size_string();
+
+        if let Some((x, y)) = self.position {
+            geometry.push_str(&format!("{:+}{:+}", x, y));
+        }
+
+        Some(geometry).filter(|geometry| !geometry.is_empty())
+    }
+}
+
+/// Profiles of isolated Chromium and Falkon web apps.
+pub fn ice_profile(codename: &str) -> PathBuf {
+    let mut xdg_data_home = home_dir();
+    xdg_data_home.push(".local/share");
+    let ice_dir = xdg_data_home.join("ice");
+
+    ice_dir.join("profiles").join(codename)
+}
+
+/// Host name the web app's page CSS and scripts are limited to.
+pub fn site_host(launcher: &WebAppLauncher) -> Option<String> {
+    Url::parse(&launcher.url)
+        .ok()?
+        .host_str()
+        .map(|host| host.to_string())
+}
+
+/// Domain whose cookies survive clearing, its subdomains included, e.g. the
+/// login pages of `example.com` for an app at `www.example.com`.
+pub fn cookie_domain(launcher: &WebAppLauncher) -> Option<String> {
+    site_host(launcher).map(|host| host.trim_start_matches("www.").to_string())
+}
+
+/// Scheme, host and port of the web app's URL, which site permissions apply to.
+pub fn site_origin(launcher: &WebAppLauncher) -> Option<(String, String, u16)> {
+    let url = Url::parse(&launcher.url).ok()?;
+
+    Some((
+        url.scheme().to_string(),
+        url.host_str()?.to_string(),
+        url.port_or_known_default()?,
+    ))
+}
+
+/// Download directory of the web app with `~` expanded, `None` keeps the browser's.
+pub fn download_dir(launcher: &WebAppLauncher) -> Option<PathBuf> {
+    let dir = launcher.download_dir.trim();
+
+    if dir.is_empty() {
+        return None;
+    }
+
+    match dir.strip_prefix("~/") {
+        Some(relative) => Some(home_dir().join(relative)),
+        None => Some(PathBuf::from(dir)),
+    }
+}
+
+/// Value of a `--flag=value` or `--flag value` argument of an `Exec` line.
+pub fn exec_arg(exec: &str, flag: &str) -> Option<String> {
+    let mut args = exec.split_whitespace();
+
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix(&format!("{}=", flag)) {
+            return Some(value.to_string());
+        }
+
+        if arg == flag {
+            return args.next().map(|value| value.to_string());
+        }
+    }
+
+    None
+}
+
+/// Quotes an `Exec` argument as the desktop entry spec asks for, when it holds
+/// spaces or characters the shell would interpret.
+pub fn quote_arg(arg: &str) -> String {
+    const RESERVED: &[char] = &[
+        ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(',
+        ')', '`',
+    ];
+
+    let arg = arg.replace('%', "%%");
+
+    if !arg.contains(RESERVED) {
+        return arg;
+    }
+
+    let mut quoted = String::from("\"");
+
+    for c in arg.chars() {
+        // the backslash itself needs escaping once more in a desktop file string
+        match c {
+            '"' | '`' | '$' => quoted.push_str(&format!("\\\\{}", c)),
+            '\\' => quoted.push_str("\\\\\\\\"),
+            _ => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+pub fn has_flag(exec: &str, flag: &str) -> bool {
+    exec.split_whitespace().any(|arg| arg == flag)
+}
+
+/// The last argument, where engines without an app mode expect the URL.
+pub fn last_url(exec: &str) -> Option<String> {
+    exec.split_whitespace()
+        .last()
+        .filter(|arg| arg.starts_with("http://") || arg.starts_with("https://"))
+        .map(|arg| arg.to_string())
+}
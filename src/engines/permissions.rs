@@ -0,0 +1,295 @@
+use serde_json::{json, Value};
+
+use super::firefox_prefs::{PrefValue, Prefs};
+
+/// Profile wide Firefox preferences older versions set for permissions, taken
+/// out of `prefs.js` since user.js no longer writes them.
+pub const STALE_PERMISSION_PREFS: [&str; 8] = [
+    "permissions.default.desktop-notification",
+    "permissions.default.camera",
+    "permissions.default.microphone",
+    "permissions.default.geo",
+    "media.autoplay.default",
+    "dom.event.clipboardevents.enabled",
+    "dom.events.asyncClipboard.readText",
+    "dom.events.asyncClipboard.clipboardItem",
+];
+
+/// Site permissions a web app can have decided before its first start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    Notifications,
+    Camera,
+    Microphone,
+    Geolocation,
+    Clipboard,
+    Autoplay,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PermissionState {
+    /// The browser asks the first time the site wants it.
+    #[default]
+    Ask,
+    Allow,
+    Block,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 6] = [
+        Permission::Notifications,
+        Permission::Camera,
+        Permission::Microphone,
+        Permission::Geolocation,
+        Permission::Clipboard,
+        Permission::Autoplay,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Permission::Notifications => "Notifications",
+            Permission::Camera => "Camera",
+            Permission::Microphone => "Microphone",
+            Permission::Geolocation => "Location",
+            Permission::Clipboard => "Clipboard",
+            Permission::Autoplay => "Autoplay",
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Permission::Notifications => "notifications",
+            Permission::Camera => "camera",
+            Permission::Microphone => "microphone",
+            Permission::Geolocation => "geolocation",
+            Permission::Clipboard => "clipboard",
+            Permission::Autoplay => "autoplay",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Permission::ALL
+            .into_iter()
+            .find(|permission| permission.as_str() == value)
+    }
+
+    /// Content setting of a Chromium profile.
+    fn chromium_content_type(&self) -> &'static str {
+        match self {
+            Permission::Notifications => "notifications",
+            Permission::Camera => "media_stream_camera",
+            Permission::Microphone => "media_stream_mic",
+            Permission::Geolocation => "geolocation",
+            Permission::Clipboard => "clipboard",
+            // muting the site is the only autoplay setting with exceptions
+            Permission::Autoplay => "sound",
+        }
+    }
+
+    /// Permission type of Firefox, clipboard access has none.
+    fn firefox_type(&self) -> Option<&'static str> {
+        match self {
+            Permission::Notifications => Some("desktop-notification"),
+            Permission::Camera => Some("camera"),
+            Permission::Microphone => Some("microphone"),
+            Permission::Geolocation => Some("geo"),
+            Permission::Clipboard => None,
+            Permission::Autoplay => Some("autoplay-media"),
+        }
+    }
+}
+
+impl PermissionState {
+    pub const ALL: [PermissionState; 3] = [
+        PermissionState::Ask,
+        PermissionState::Allow,
+        PermissionState::Block,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PermissionState::Ask => "Ask",
+            PermissionState::Allow => "Allow",
+            PermissionState::Block => "Block",
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            PermissionState::Ask => "ask",
+            PermissionState::Allow => "allow",
+            PermissionState::Block => "block",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        PermissionState::ALL
+            .into_iter()
+            .find(|state| state.as_str() == value)
+            .unwrap_or_default()
+    }
+}
+
+/// Permissions the user decided on, anything missing is left to ask.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Permissions {
+    states: Vec<(Permission, PermissionState)>,
+}
+
+impl Permissions {
+    pub fn get(&self, permission: Permission) -> PermissionState {
+        self.states
+            .iter()
+            .find(|(p, _)| *p == permission)
+            .map(|(_, state)| *state)
+            .unwrap_or_default()
+    }
+
+    pub fn set(&mut self, permission: Permission, state: PermissionState) {
+        self.states.retain(|(p, _)| *p != permission);
+
+        if state != PermissionState::Ask {
+            self.states.push((permission, state));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Reads the pairs of the `X-WebApp-Permissions` key.
+    pub fn from_pairs(pairs: &[(String, String)]) -> Self {
+        let mut permissions = Permissions::default();
+
+        for (key, value) in pairs {
+            if let Some(permission) = Permission::parse(key) {
+                permissions.set(permission, PermissionState::parse(value));
+            }
+        }
+
+        permissions
+    }
+
+    pub fn to_pairs(&self) -> Vec<(String, String)> {
+        self.states
+            .iter()
+            .map(|(permission, state)| {
+                (permission.as_str().to_string(), state.as_str().to_string())
+            })
+            .collect()
+    }
+
+    /// `profile.content_settings.exceptions` of a Chromium profile for the
+    /// `https://example.com:443,*` pattern of the web app's origin.
+    pub fn chromium_exceptions(&self, pattern: &str) -> Value {
+        let mut exceptions = json!({});
+
+        for (permission, state) in &self.states {
+            let setting = match (permission, state) {
+                // allowing autoplay is a command line switch
+                (Permission::Autoplay, PermissionState::Allow) => continue,
+                (_, PermissionState::Allow) => 1,
+                (_, PermissionState::Block) => 2,
+                (_, PermissionState::Ask) => continue,
+            };
+
+            exceptions[permission.chromium_content_type()][pattern] = json!({ "setting": setting });
+        }
+
+        json!({
+            "profile": {
+                "content_settings": {
+                    "exceptions": exceptions,
+                },
+            },
+        })
+    }
+
+    /// Content settings without an exception of the web app, earlier ones
+    /// have to be removed from the profile.
+    pub fn chromium_cleared(&self) -> Vec<&'static str> {
+        Permission::ALL
+            .into_iter()
+            .filter(|permission| {
+                matches!(
+                    (permission, self.get(*permission)),
+                    (_, PermissionState::Ask) | (Permission::Autoplay, PermissionState::Allow)
+                )
+            })
+            .map(|permission| permission.chromium_content_type())
+            .collect()
+    }
+
+    /// Lines of the Firefox default permissions file for the web app's host.
+    pub fn firefox_permissions(&self, host: &str) -> String {
+        let mut permissions = String::new();
+
+        for (permission, state) in &self.states {
+            let Some(permission_type) = permission.firefox_type() else {
+                continue;
+            };
+
+            let value = match (permission, state) {
+                (_, PermissionState::Allow) => 1,
+                // blocks audible and inaudible media
+                (Permission::Autoplay, PermissionState::Block) => 5,
+                (_, PermissionState::Block) => 2,
+                (_, PermissionState::Ask) => continue,
+            };
+
+            permissions.push_str(&format!("host\t{}\t{}\t{}\n", permission_type, value, host));
+        }
+
+        permissions
+    }
+
+    /// Profile wide preferences of Firefox. Older versions changed the
+    /// permission defaults, they go back to asking. Clipboard access has no
+    /// site permission, allowing it turns reading on for the whole profile.
+    pub fn firefox_prefs(&self) -> Prefs {
+        let mut prefs = Prefs::new();
+
+        if self.get(Permission::Clipboard) == PermissionState::Allow {
+            prefs.set("dom.events.asyncClipboard.readText", PrefValue::Bool(true));
+            prefs.set(
+                "dom.events.asyncClipboard.clipboardItem",
+                PrefValue::Bool(true),
+            );
+        }
+
+        prefs
+    }
+
+    /// `config.py` lines of qutebrowser, limited to the `*://example.com/*` pattern.
+    pub fn qutebrowser_config(&self, pattern: &str) -> String {
+        let mut config = String::new();
+
+        for (permission, state) in &self.states {
+            let allowed = *state == PermissionState::Allow;
+            let value = if allowed { "True" } else { "False" };
+
+            let (setting, value) = match permission {
+                Permission::Notifications => ("content.notifications.enabled", value),
+                Permission::Camera => ("content.media.video_capture", value),
+                Permission::Microphone => ("content.media.audio_capture", value),
+                Permission::Geolocation => ("content.geolocation", value),
+                Permission::Clipboard if allowed => {
+                    ("content.javascript.clipboard", "'access-paste'")
+                }
+                Permission::Clipboard => ("content.javascript.clipboard", "'none'"),
+                // qutebrowser only has a global setting
+                Permission::Autoplay => {
+                    config.push_str(&format!("c.content.autoplay = {}\n", value));
+                    continue;
+                }
+            };
+
+            config.push_str(&format!(
+                "config.set('{}', {}, '{}')\n",
+                setting, value, pattern
+            ));
+        }
+
+        config
+    }
+}
@@ -0,0 +1,178 @@
+use super::{
+    firefox_prefs::{PrefValue, Prefs},
+    quote_arg,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProxyMode {
+    /// Whatever the desktop's network settings say, as browsers do by default.
+    #[default]
+    System,
+    /// A direct connection, ignoring the desktop's settings.
+    Direct,
+    Http,
+    Socks,
+    /// A proxy auto-config script.
+    Pac,
+}
+
+impl ProxyMode {
+    pub const ALL: [ProxyMode; 5] = [
+        ProxyMode::System,
+        ProxyMode::Direct,
+        ProxyMode::Http,
+        ProxyMode::Socks,
+        ProxyMode::Pac,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProxyMode::System => "System proxy",
+            ProxyMode::Direct => "No proxy",
+            ProxyMode::Http => "HTTP proxy",
+            ProxyMode::Socks => "SOCKS proxy",
+            ProxyMode::Pac => "Proxy script (PAC)",
+        }
+    }
+
+    /// Value of the `X-WebApp-Proxy` key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProxyMode::System => "system",
+            ProxyMode::Direct => "none",
+            ProxyMode::Http => "http",
+            ProxyMode::Socks => "socks",
+            ProxyMode::Pac => "pac",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        ProxyMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == value)
+            .unwrap_or_default()
+    }
+
+    /// Manual proxies take a host, a port and hosts to bypass them.
+    pub fn is_manual(&self) -> bool {
+        matches!(self, ProxyMode::Http | ProxyMode::Socks)
+    }
+}
+
+/// Proxy of a web app, only its own traffic goes through it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Proxy {
+    pub mode: ProxyMode,
+    pub host: String,
+    pub port: Option<u16>,
+    pub bypass: Vec<String>,
+    pub pac_url: String,
+}
+
+impl Proxy {
+    /// Browsers apply a proxy to everything running in the same profile.
+    pub fn needs_isolation(&self) -> bool {
+        self.mode != ProxyMode::System
+    }
+
+    /// The mode which actually applies, incomplete settings fall back to the system.
+    fn effective_mode(&self) -> ProxyMode {
+        match self.mode {
+            ProxyMode::Http | ProxyMode::Socks if self.host.is_empty() || self.port.is_none() => {
+                ProxyMode::System
+            }
+            ProxyMode::Pac if self.pac_url.is_empty() => ProxyMode::System,
+            mode => mode,
+        }
+    }
+
+    fn server(&self) -> String {
+        format!("{}:{}", self.host, self.port.unwrap_or_default())
+    }
+
+    /// Command line switches of Chromium and the browsers built on it.
+    pub fn chromium_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        match self.effective_mode() {
+            ProxyMode::System => {}
+            ProxyMode::Direct => args.push(String::from("--no-proxy-server")),
+            ProxyMode::Pac => args.push(quote_arg(&format!("--proxy-pac-url={}", self.pac_url))),
+            mode => {
+                let scheme = if mode == ProxyMode::Socks {
+                    "socks5"
+                } else {
+                    "http"
+                };
+
+                let server = format!("--proxy-server={}://{}", scheme, self.server());
+                args.push(quote_arg(&server));
+
+                if !self.bypass.is_empty() {
+                    let bypass = format!("--proxy-bypass-list={}", self.bypass.join(";"));
+                    args.push(quote_arg(&bypass));
+                }
+            }
+        }
+
+        args
+    }
+
+    /// `network.proxy.*` preferences of a Firefox profile.
+    pub fn firefox_prefs(&self) -> Prefs {
+        let mut prefs = Prefs::new();
+
+        // set even for the system proxy, an earlier manual one stays in prefs.js
+        let proxy_type = match self.effective_mode() {
+            ProxyMode::Direct => 0,
+            ProxyMode::Http | ProxyMode::Socks => 1,
+            ProxyMode::Pac => 2,
+            ProxyMode::System => 5,
+        };
+        prefs.set("network.proxy.type", PrefValue::Int(proxy_type));
+
+        let port = PrefValue::Int(self.port.unwrap_or_default().into());
+
+        match self.effective_mode() {
+            ProxyMode::Http => {
+                prefs.set("network.proxy.http", PrefValue::String(self.host.clone()));
+                prefs.set("network.proxy.http_port", port.clone());
+                prefs.set("network.proxy.ssl", PrefValue::String(self.host.clone()));
+                prefs.set("network.proxy.ssl_port", port);
+            }
+            ProxyMode::Socks => {
+                prefs.set("network.proxy.socks", PrefValue::String(self.host.clone()));
+                prefs.set("network.proxy.socks_port", port);
+                prefs.set("network.proxy.socks_version", PrefValue::Int(5));
+                prefs.set("network.proxy.socks_remote_dns", PrefValue::Bool(true));
+            }
+            ProxyMode::Pac => {
+                prefs.set(
+                    "network.proxy.autoconfig_url",
+                    PrefValue::String(self.pac_url.clone()),
+                );
+            }
+            ProxyMode::System | ProxyMode::Direct => {}
+        }
+
+        if self.effective_mode().is_manual() {
+            prefs.set(
+                "network.proxy.no_proxies_on",
+                PrefValue::String(self.bypass.join(", ")),
+            );
+        }
+
+        prefs
+    }
+
+    /// Value of qutebrowser's `content.proxy` setting.
+    pub fn qutebrowser_proxy(&self) -> String {
+        match self.effective_mode() {
+            ProxyMode::System => String::from("system"),
+            ProxyMode::Direct => String::from("none"),
+            ProxyMode::Http => format!("http://{}", self.server()),
+            ProxyMode::Socks => format!("socks://{}", self.server()),
+            ProxyMode::Pac => format!("pac+{}", self.pac_url),
+        }
+    }
+}
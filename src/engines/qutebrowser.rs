@@ -0,0 +1,262 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::Write,
+    path::PathBuf,
+};
+
+use anyhow::Result;
+
+use crate::{common::WebAppLauncher, languages::language_tag, profiles::profile_root};
+
+use super::{
+    download_dir, environment::DisplayBackend, exec_arg, has_flag, last_url, site_host, Engine,
+    EngineOption, Retention, WindowMode,
+};
+
+/// Keyboard driven QtWebEngine browser, configured with a `config.py` per basedir.
+pub struct Qutebrowser;
+
+impl Qutebrowser {
+    fn basedir(&self, launcher: &WebAppLauncher) -> PathBuf {
+        profile_root(&launcher.web_browser, "qutebrowser").join(&launcher.codename)
+    }
+
+    fn config(&self, launcher: &WebAppLauncher) -> String {
+        let (tabs, statusbar) = if launcher.window_mode.navbar(launcher.navbar) {
+            ("multiple", "always")
+        } else {
+            ("never", "never")
+        };
+
+        let mut config = String::from("# Generated by COSMIC Web Apps, changes are overwritten.\n");
+        config.push_str("config.load_autoconfig(False)\n");
+        config.push_str(&format!("c.tabs.show = '{}'\n", tabs));
+        config.push_str(&format!("c.statusbar.show = '{}'\n", statusbar));
+        config.push_str("c.window.title_format = '{current_title}'\n");
+        config.push_str(&format!(
+            "c.url.start_pages = ['{}']\n",
+            python_string(&launcher.url)
+        ));
+
+        if launcher.is_incognito {
+            config.push_str("c.content.private_browsing = True\n");
+        }
+
+        config.push_str(&format!("c.zoom.default = '{}%'\n", launcher.zoom));
+
+        if launcher.force_dark {
+            config.push_str("c.colors.webpage.preferred_color_scheme = 'dark'\n");
+            config.push_str("c.colors.webpage.darkmode.enabled = True\n");
+        }
+
+        if let Some(dir) = download_dir(launcher) {
+            config.push_str(&format!(
+                "c.downloads.location.directory = '{}'\n",
+                python_string(&dir.display().to_string())
+            ));
+            config.push_str(&format!(
+                "c.downloads.location.prompt = {}\n",
+                if launcher.download_ask {
+                    "True"
+                } else {
+                    "False"
+                }
+            ));
+        }
+
+        if !launcher.languages.is_empty() {
+            config.push_str(&format!(
+                "c.content.headers.accept_language = '{}'\n",
+                python_string(&launcher.languages.join(","))
+            ));
+        }
+
+        let mut dictionaries: Vec<String> = Vec::new();
+
+        for dictionary in &launcher.dictionaries {
+            match dictionary_name(dictionary) {
+                Some(name) if !dictionaries.contains(&name) => dictionaries.push(name),
+                Some(_) => {}
+                None => tracing::warn!("qutebrowser has no {} dictionary.", dictionary),
+            }
+        }
+
+        if !dictionaries.is_empty() {
+            let dictionaries: Vec<String> = dictionaries
+                .iter()
+                .map(|dictionary| format!("'{}'", dictionary))
+                .collect();
+
+            // qutebrowser needs its own copies, installed with dictcli.py
+            config.push_str(&format!(
+                "c.spellcheck.languages = [{}]\n",
+                dictionaries.join(", ")
+            ));
+        }
+
+        if let Some(host) = site_host(launcher) {
+            config.push_str(
+                &launcher
+                    .permissions
+                    .qutebrowser_config(&format!("*://{}/*", python_string(&host))),
+            );
+        }
+
+        // qutebrowser has no list of hosts to bypass the proxy
+        config.push_str(&format!(
+            "c.content.proxy = '{}'\n",
+            launcher.proxy.qutebrowser_proxy().replace('\'', "%27")
+        ));
+
+        if !launcher.user_agent.is_empty() {
+            config.push_str(&format!(
+                "c.content.headers.user_agent = '{}'\n",
+                python_string(&launcher.user_agent)
+            ));
+        }
+
+        config
+    }
+}
+
+/// Dictionaries dictcli.py can install.
+const DICTIONARIES: &[&str] = &[
+    "af-ZA", "bg-BG", "ca-ES", "cs-CZ", "da-DK", "de-DE", "el-GR", "en-AU", "en-CA", "en-GB",
+    "en-US", "es-ES", "et-EE", "fa-IR", "fo-FO", "fr-FR", "he-IL", "hi-IN", "hr-HR", "hu-HU",
+    "id-ID", "it-IT", "ko", "lt-LT", "lv-LV", "nb-NO", "nl-NL", "pl-PL", "pt-BR", "pt-PT", "ro-RO",
+    "ru-RU", "sh", "sk-SK", "sl-SI", "sq", "sr", "sv-SE", "ta-IN", "tg-TG", "tr-TR", "uk-UA",
+    "vi-VN",
+];
+
+/// qutebrowser's name for a hunspell dictionary, e.g. `en-US` for `en_US`.
+fn dictionary_name(dictionary: &str) -> Option<String> {
+    let tag = language_tag(dictionary)?;
+    let language = tag.split('-').next()?;
+
+    // a few come without a region, e.g. `sr` for `sr_RS`
+    DICTIONARIES
+        .iter()
+        .find(|name| **name == tag)
+        .or_else(|| DICTIONARIES.iter().find(|name| **name == language))
+        .map(|name| name.to_string())
+}
+
+/// Escapes text for a single quoted string of `config.py`.
+fn python_string(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+impl Engine for Qutebrowser {
+    fn options(&self) -> Vec<EngineOption> {
+        vec![
+            EngineOption::PrivateWindow,
+            EngineOption::Navbar,
+            EngineOption::WindowMode,
+            EngineOption::WindowGeometry,
+            EngineOption::UserAgent,
+            EngineOption::Proxy,
+            EngineOption::Zoom,
+            EngineOption::ForceDark,
+            EngineOption::Downloads,
+            EngineOption::Languages,
+            EngineOption::Permissions,
+            EngineOption::Retention,
+            EngineOption::Environment,
+            EngineOption::DisplayBackend,
+        ]
+    }
+
+    /// Cookies are stored or not for all sites alike.
+    fn retention_modes(&self) -> Vec<Retention> {
+        vec![Retention::Keep, Retention::ClearCache, Retention::Ephemeral]
+    }
+
+    fn cache_dirs(&self, launcher: &WebAppLauncher) -> Vec<PathBuf> {
+        if launcher.retention.clears_cache() {
+            vec![self.basedir(launcher).join("cache")]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn profile_path(&self, launcher: &WebAppLauncher) -> Option<PathBuf> {
+        Some(self.basedir(launcher))
+    }
+
+    fn create_profile(&self, launcher: &WebAppLauncher) -> Result<()> {
+        let config_dir = self.basedir(launcher).join("config");
+
+        create_dir_all(&config_dir)?;
+
+        let mut file = File::create(config_dir.join("config.py"))?;
+        file.write_all(self.config(launcher).as_bytes())?;
+
+        Ok(())
+    }
+
+    fn backend_env(&self, backend: DisplayBackend) -> Vec<(String, String)> {
+        backend
+            .qt_platform()
+            .map(|platform| (String::from("QT_QPA_PLATFORM"), platform.to_string()))
+            .into_iter()
+            .collect()
+    }
+
+    fn private_flag(&self, _launcher: &WebAppLauncher) -> &'static str {
+        // private browsing is part of the generated config
+        ""
+    }
+
+    fn exec_string(&self, launcher: &WebAppLauncher) -> String {
+        let basedir = self.basedir(launcher);
+        let basedir = basedir.to_str().unwrap();
+
+        // the desktop file name is the Wayland app id, the Qt name the X11 class
+        let mut exec_string = format!(
+            "{} --basedir {} --target window --desktop-file-name {} --qt-arg name {} ",
+            launcher.exec,
+            basedir,
+            self.wm_class(launcher),
+            self.wm_class(launcher)
+        );
+
+        // handed to Qt as is, maximizing is left to the window manager
+        if let Some(geometry) = launcher
+            .window_geometry
+            .x11_geometry()
+            .filter(|_| launcher.window_mode.has_geometry())
+        {
+            exec_string.push_str(&format!("--qt-arg qwindowgeometry {} ", geometry));
+        }
+
+        if !launcher.custom_parameters.is_empty() {
+            exec_string.push_str(&format!("{} ", launcher.custom_parameters));
+        }
+
+        // commands before the URL run once the window is open
+        if matches!(
+            launcher.window_mode,
+            WindowMode::Kiosk | WindowMode::Fullscreen
+        ) {
+            exec_string.push_str(":fullscreen ");
+        }
+
+        exec_string.push_str(&launcher.url);
+
+        exec_string
+    }
+
+    fn parse_launcher(&self, launcher: &mut WebAppLauncher) {
+        if launcher.url.is_empty() {
+            if let Some(url) = last_url(&launcher.exec) {
+                launcher.url = url;
+            }
+        }
+
+        launcher.isolate_profile |= exec_arg(&launcher.exec, "--basedir").is_some();
+
+        if has_flag(&launcher.exec, ":fullscreen") && launcher.window_mode == WindowMode::App {
+            launcher.window_mode = WindowMode::Fullscreen;
+        }
+    }
+}
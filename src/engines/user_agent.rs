@@ -0,0 +1,25 @@
+/// User agents offered in the creator, for sites which only serve some browsers
+/// or keep their mobile layout for phones.
+pub const PRESETS: [(&str, &str); 4] = [
+    (
+        "Desktop Chrome",
+        "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36",
+    ),
+    (
+        "Desktop Firefox",
+        "Mozilla/5.0 (X11; Linux x86_64; rv:132.0) Gecko/20100101 Firefox/132.0",
+    ),
+    (
+        "Android",
+        "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Mobile Safari/537.36",
+    ),
+    (
+        "iPhone",
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 18_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.0 Mobile/15E148 Safari/604.1",
+    ),
+];
+
+/// Index of the preset `user_agent` is, if it is not custom text.
+pub fn preset(user_agent: &str) -> Option<usize> {
+    PRESETS.iter().position(|(_, preset)| *preset == user_agent)
+}
@@ -16,15 +16,20 @@ use cosmic_files::dialog::{Dialog, DialogKind, DialogMessage, DialogResult};
 use crate::{
     add_icon_packs_install_script,
     common::{
-        self, Browser, find_icons, get_icon_name_from_url, get_supported_browsers,
+        self, find_icons, get_icon_name_from_url, get_supported_browsers, refresh_browsers,
         icon_cache_get, image_handle, move_icon, search_user_icons, WebAppLauncher,
     },
     creator, execute_script,
     home_screen::Home,
+    icon_composer::{
+        create_monogram, load_source, monogram_icon, save_composed, SourceIcon, MONOGRAM_PATH,
+    },
+    icon_editor::{self, IconEditor},
     icon_pack_installed,
     iconpicker::{self, IconPicker},
     icons_installator::Installator,
-    warning::WarnMessages,
+    settings::{self, Settings},
+    warning::{Warning, WarnMessages},
 };
 
 #[derive(Debug, Clone)]
@@ -39,6 +44,8 @@ pub enum Buttons {
 pub enum Message {
     OpenHome,
     OpenCreator,
+    OpenSettings,
+    Settings(settings::Message),
     CloseCreator,
     OpenIconPicker,
     OpenIconPickerDialog,
@@ -46,6 +53,7 @@ pub enum Message {
     OpenFileResult(DialogResult),
     Creator(creator::Message),
     Result,
+    ExtensionsInstalled(Result<(), String>),
 
     Clicked(Buttons),
     // icons
@@ -53,9 +61,14 @@ pub enum Message {
     CustomIconsSearch(String),
     FoundIcons(Vec<String>),
     PushIcon(Option<iconpicker::Icon>),
+    PushMonogram((iconpicker::Icon, String), bool),
     ChangeIcon(iconpicker::Icon),
     SetIcon(iconpicker::Icon),
     SelectIcon(iconpicker::Icon),
+    OpenIconEditor,
+    IconEditor(icon_editor::Message),
+    IconEditorSource(Option<SourceIcon>),
+    SaveEditedIcon,
 
     // Installator
     InstallScript(String),
@@ -67,7 +80,9 @@ pub enum Pages {
     MainWindow,
     AppCreator,
     IconPicker(IconPicker),
+    IconEditor(IconEditor),
     IconInstallator(Installator),
+    Settings(Settings),
 }
 
 pub struct Window {
@@ -78,6 +93,21 @@ pub struct Window {
     dialog_opt: Option<Dialog<Message>>,
 }
 
+impl Window {
+    /// Copies the chosen icon to the user icons, the monogram is written there
+    /// only now.
+    fn save_icon(&self, icon: &iconpicker::Icon) -> String {
+        let title = self.creator_window.app_title.clone();
+
+        match &self.creator_window.monogram {
+            Some(svg) if icon.path == MONOGRAM_PATH => {
+                save_composed(svg, format!("{} monogram", title)).unwrap_or_default()
+            }
+            _ => move_icon(icon.path.clone(), title),
+        }
+    }
+}
+
 impl cosmic::Application for Window {
     type Executor = executor::Default;
     type Flags = ();
@@ -125,6 +155,7 @@ impl cosmic::Application for Window {
     fn header_start(&self) -> Vec<Element<Self::Message>> {
         let go_home_icon = icon_cache_get("go-home-symbolic", 16);
         let go_creator = icon_cache_get("document-new-symbolic", 16);
+        let go_settings = icon_cache_get("preferences-system-symbolic", 16);
         let cosmic_theme::Spacing { space_xxs, .. } = self.core().system_theme().cosmic().spacing;
 
         vec![
@@ -138,6 +169,11 @@ impl cosmic::Application for Window {
                 .padding(space_xxs)
                 .style(style::Button::Icon)
                 .into(),
+            widget::button(go_settings)
+                .on_press(Message::OpenSettings)
+                .padding(space_xxs)
+                .style(style::Button::Icon)
+                .into(),
         ]
     }
 
@@ -153,7 +189,9 @@ impl cosmic::Application for Window {
                 vec![text(title).into()]
             }
             Pages::IconPicker(_) => vec![text("Icon selector").into()],
+            Pages::IconEditor(_) => vec![text("Icon editor").into()],
             Pages::IconInstallator(_) => vec![text("Papirus Icons installator").into()],
+            Pages::Settings(_) => vec![text("Settings").into()],
         }
     }
 
@@ -170,6 +208,26 @@ impl cosmic::Application for Window {
 
                 Command::none()
             }
+            Message::OpenSettings => {
+                self.current_page = Pages::Settings(Settings::new());
+
+                Command::none()
+            }
+            Message::Settings(message) => {
+                if let Pages::Settings(ref mut settings) = self.current_page {
+                    let saved = matches!(message, settings::Message::Save);
+                    let command = settings.update(message);
+
+                    if saved {
+                        refresh_browsers();
+                        self.creator_window.app_browsers = get_supported_browsers();
+                    }
+
+                    return command.map(|mess| app(Message::Settings(mess)));
+                }
+
+                Command::none()
+            }
             Message::CloseCreator => {
                 self.current_page = Pages::MainWindow;
                 self.creator_window.edit_mode = false;
@@ -231,57 +289,131 @@ impl cosmic::Application for Window {
                 Command::none()
             }
             Message::Result => {
-                let launcher = if let Some(launcher) = self.main_window.launcher.to_owned() {
-                    let _ = launcher.delete();
-
-                    WebAppLauncher::new(
-                        self.creator_window.app_title.clone(),
-                        Some(launcher.codename),
-                        self.creator_window.app_url.clone(),
-                        self.creator_window.app_icon.clone(),
-                        self.creator_window.app_category.clone(),
-                        self.creator_window.app_browser.clone(),
-                        self.creator_window.app_parameters.clone(),
-                        self.creator_window.app_isolated,
-                        self.creator_window.app_navbar,
-                        self.creator_window.app_incognito,
-                    )
-                } else {
-                    WebAppLauncher::new(
-                        self.creator_window.app_title.clone(),
+                if self.creator_window.app_icon.is_empty() {
+                    if let Ok(monogram) = create_monogram(
+                        &self.creator_window.app_title,
+                        &self.creator_window.app_url,
                         None,
-                        self.creator_window.app_url.clone(),
-                        self.creator_window.app_icon.clone(),
-                        self.creator_window.app_category.clone(),
-                        self.creator_window.app_browser.clone(),
-                        self.creator_window.app_parameters.clone(),
-                        self.creator_window.app_isolated,
-                        self.creator_window.app_navbar,
-                        self.creator_window.app_incognito,
-                    )
-                };
+                    ) {
+                        self.creator_window.app_icon = monogram;
+                    }
+                }
+
+                let codename = self
+                    .main_window
+                    .launcher
+                    .as_ref()
+                    .map(|launcher| launcher.codename.clone());
+
+                let mut launcher = WebAppLauncher::new(
+                    self.creator_window.app_title.clone(),
+                    codename,
+                    self.creator_window.app_url.clone(),
+                    self.creator_window.app_icon.clone(),
+                    self.creator_window.app_category.clone(),
+                    self.creator_window.app_browser.clone(),
+                    self.creator_window.app_parameters.clone(),
+                    self.creator_window.app_isolated,
+                    self.creator_window.app_navbar,
+                    self.creator_window.app_incognito,
+                );
+
+                launcher
+                    .firefox_prefs
+                    .clone_from(&self.creator_window.app_firefox_prefs);
+                launcher
+                    .firefox_chrome
+                    .clone_from(&self.creator_window.app_firefox_chrome);
+                launcher.firefox_chrome.custom_css = self.creator_window.app_chrome_css.lines();
+                launcher.content_css = self.creator_window.app_content_css.text();
+                launcher.user_script = self.creator_window.app_user_script.text();
+                launcher
+                    .chromium_prefs
+                    .clone_from(&self.creator_window.app_chromium_prefs);
+                launcher.chromium_extensions = self
+                    .creator_window
+                    .app_chromium_extensions
+                    .split(',')
+                    .map(|extension| extension.trim().to_string())
+                    .filter(|extension| !extension.is_empty())
+                    .collect();
+                launcher.firefox_extensions = self
+                    .creator_window
+                    .app_firefox_extensions
+                    .split(',')
+                    .map(|extension| extension.trim().to_string())
+                    .filter(|extension| !extension.is_empty())
+                    .collect();
+                launcher.window_mode = self.creator_window.app_window_mode;
+                launcher.window_geometry = self.creator_window.window_geometry();
+                launcher.user_agent = self.creator_window.app_user_agent.trim().to_string();
+                launcher.proxy = self.creator_window.proxy();
+                launcher.zoom = self.creator_window.app_zoom;
+                launcher.force_dark = self.creator_window.app_force_dark;
+                launcher.download_dir = self.creator_window.app_download_dir.trim().to_string();
+                launcher.download_ask = self.creator_window.app_download_ask;
+                launcher
+                    .languages
+                    .clone_from(&self.creator_window.app_languages);
+                launcher
+                    .dictionaries
+                    .clone_from(&self.creator_window.app_dictionaries);
+                launcher
+                    .permissions
+                    .clone_from(&self.creator_window.app_permissions);
+                launcher.retention = self.creator_window.app_retention;
+                launcher
+                    .environment
+                    .clone_from(&self.creator_window.app_environment);
+                launcher.backend = self.creator_window.app_backend;
 
                 if launcher.is_valid {
+                    // the old launcher only goes once the new one can replace it
+                    if let Some(previous) = &self.main_window.launcher {
+                        // the profile stays unless it belongs to another browser now
+                        if previous.web_browser.id == launcher.web_browser.id {
+                            let _ = previous.delete_entry();
+                        } else {
+                            let _ = previous.delete();
+                        }
+                    }
+
                     let _ = move_icon(
                         self.creator_window.app_icon.clone(),
                         self.creator_window.app_title.clone(),
                     );
 
-                    let _ = launcher.create();
+                    let _ = launcher.create(self.main_window.launcher.as_ref());
                     self.creator_window.edit_mode = false;
                     self.current_page = Pages::MainWindow;
+
+                    return Command::perform(launcher.install_extensions(), |result| {
+                        app(Message::ExtensionsInstalled(result))
+                    });
                 } else {
                     self.creator_window.warning.show = true;
                 }
 
                 Command::none()
             }
+            Message::ExtensionsInstalled(result) => {
+                match result {
+                    Ok(_) => self.main_window.warning.remove_all_warns(),
+                    Err(e) => {
+                        self.main_window.warning =
+                            Warning::new(vec![WarnMessages::Extensions(e)], true);
+                    }
+                }
+
+                Command::none()
+            }
 
             Message::Clicked(buttons) => match buttons {
                 Buttons::Edit(launcher) => {
-                    let selected_browser = get_supported_browsers()
+                    let browsers = get_supported_browsers();
+                    let selected_browser = browsers
                         .iter()
-                        .position(|b| b.name == launcher.web_browser.name);
+                        .position(|b| b.id == launcher.web_browser.id);
 
                     self.creator_window.warning.remove_all_warns();
                     self.main_window.edit_mode = true;
@@ -292,11 +424,55 @@ impl cosmic::Application for Window {
                     self.creator_window.app_icon.clone_from(&launcher.icon);
                     self.creator_window.app_parameters = launcher.custom_parameters;
                     self.creator_window.app_category = launcher.category;
-                    self.creator_window.app_browser =
-                        Browser::web_browser(launcher.web_browser.name).expect("browser not found");
-                    self.creator_window.selected_browser = selected_browser;
+
+                    // the browser may be gone since, e.g. uninstalled or renamed
+                    match selected_browser {
+                        Some(idx) => {
+                            self.creator_window.app_browser = browsers[idx].clone();
+                            self.creator_window.selected_browser = Some(idx);
+                        }
+                        None => {
+                            self.creator_window.app_browser = browsers[0].clone();
+                            self.creator_window.selected_browser = Some(0);
+                            self.creator_window
+                                .warning
+                                .push_warn(WarnMessages::AppBrowser);
+                        }
+                    }
+
                     self.creator_window.app_navbar = launcher.navbar;
                     self.creator_window.app_incognito = launcher.is_incognito;
+                    self.creator_window.app_isolated = launcher.isolate_profile;
+                    self.creator_window.app_firefox_prefs = launcher.firefox_prefs;
+                    self.creator_window.app_chrome_css = creator::TextArea::with_text(
+                        &launcher.firefox_chrome.custom_css.join("\n"),
+                    );
+                    self.creator_window.app_firefox_chrome = launcher.firefox_chrome;
+                    self.creator_window.app_content_css =
+                        creator::TextArea::with_text(&launcher.content_css);
+                    self.creator_window.app_user_script =
+                        creator::TextArea::with_text(&launcher.user_script);
+                    self.creator_window.app_chromium_prefs = launcher.chromium_prefs;
+                    self.creator_window.app_chromium_extensions =
+                        launcher.chromium_extensions.join(", ");
+                    self.creator_window.app_firefox_extensions =
+                        launcher.firefox_extensions.join(", ");
+                    self.creator_window.app_window_mode = launcher.window_mode;
+                    self.creator_window
+                        .set_window_geometry(&launcher.window_geometry);
+                    self.creator_window.app_user_agent = launcher.user_agent;
+                    self.creator_window.set_proxy(&launcher.proxy);
+                    self.creator_window.app_zoom = launcher.zoom;
+                    self.creator_window.app_force_dark = launcher.force_dark;
+                    self.creator_window.app_download_dir = launcher.download_dir;
+                    self.creator_window.app_download_ask = launcher.download_ask;
+                    self.creator_window.app_languages = launcher.languages;
+                    self.creator_window.app_dictionaries = launcher.dictionaries;
+                    self.creator_window.app_permissions = launcher.permissions;
+                    self.creator_window.app_retention = launcher.retention;
+                    self.creator_window.update_retention_modes();
+                    self.creator_window.app_environment = launcher.environment;
+                    self.creator_window.app_backend = launcher.backend;
                     self.creator_window.edit_mode = true;
 
                     Command::perform(image_handle(launcher.icon), |result| {
@@ -354,6 +530,7 @@ impl cosmic::Application for Window {
             }
             Message::FoundIcons(result) => {
                 let mut commands: Vec<Command<CosmicMessage<Message>>> = Vec::new();
+                let fallback = result.is_empty();
 
                 result.into_iter().for_each(|path| {
                     commands.push(Command::perform(image_handle(path), |result| {
@@ -361,13 +538,19 @@ impl cosmic::Application for Window {
                     }));
                 });
 
+                commands.push(Command::perform(
+                    monogram_icon(
+                        self.creator_window.app_title.clone(),
+                        self.creator_window.app_url.clone(),
+                    ),
+                    move |result| app(Message::PushMonogram(result, fallback)),
+                ));
+
                 Command::batch(commands)
             }
             Message::PushIcon(icon) => {
                 if self.creator_window.selected_icon.is_none() && icon.is_some() {
-                    let path = icon.as_ref().unwrap().path.clone();
-                    let saved = move_icon(path, self.creator_window.app_title.clone());
-                    self.creator_window.app_icon.clone_from(&saved);
+                    let saved = self.save_icon(icon.as_ref().unwrap());
                     self.creator_window.app_icon = saved;
                     self.creator_window.selected_icon.clone_from(&icon);
 
@@ -385,9 +568,23 @@ impl cosmic::Application for Window {
 
                 Command::none()
             }
+            Message::PushMonogram((icon, svg), fallback) => {
+                self.creator_window.monogram = Some(svg);
+
+                if fallback {
+                    return self.update(Message::PushIcon(Some(icon)));
+                }
+
+                if let Pages::IconPicker(ref mut picker) = self.current_page {
+                    if !picker.icons.contains(&icon) {
+                        picker.icons.push(icon);
+                    }
+                }
+
+                Command::none()
+            }
             Message::ChangeIcon(icon) => {
-                let path = icon.path.clone();
-                let saved = move_icon(path, self.creator_window.app_title.clone());
+                let saved = self.save_icon(&icon);
                 self.creator_window.selected_icon = Some(icon.clone());
                 self.creator_window.app_icon = saved;
                 self.current_page = Pages::AppCreator;
@@ -425,6 +622,60 @@ impl cosmic::Application for Window {
 
                 Command::none()
             }
+            Message::OpenIconEditor => {
+                if self.creator_window.app_icon.is_empty() {
+                    return Command::none();
+                }
+
+                self.current_page = Pages::IconEditor(IconEditor::new());
+
+                Command::perform(
+                    load_source(self.creator_window.app_icon.clone()),
+                    |result| app(Message::IconEditorSource(result)),
+                )
+            }
+            Message::IconEditor(message) => {
+                if let Pages::IconEditor(ref mut editor) = self.current_page {
+                    let command = editor.update(message);
+
+                    return command.map(|mess| app(Message::IconEditor(mess)));
+                }
+
+                Command::none()
+            }
+            Message::IconEditorSource(source) => {
+                if let Pages::IconEditor(ref mut editor) = self.current_page {
+                    match source {
+                        Some(source) => editor.set_source(source),
+                        None => self.current_page = Pages::AppCreator,
+                    }
+                }
+
+                Command::none()
+            }
+            Message::SaveEditedIcon => {
+                if let Pages::IconEditor(ref editor) = self.current_page {
+                    if let Some(svg) = &editor.composed {
+                        match save_composed(svg, self.creator_window.app_title.clone()) {
+                            Ok(saved) => {
+                                let handle =
+                                    widget::svg::Handle::from_memory(svg.clone().into_bytes());
+                                let icon =
+                                    iconpicker::Icon::new(iconpicker::IconType::Svg(handle), saved);
+
+                                self.current_page = Pages::AppCreator;
+
+                                return Command::perform(async {}, |_| {
+                                    app(Message::SelectIcon(icon))
+                                });
+                            }
+                            Err(e) => tracing::error!("Cannot save edited icon: {}", e),
+                        }
+                    }
+                }
+
+                Command::none()
+            }
 
             Message::InstallScript(script) => {
                 if !icon_pack_installed() {
@@ -449,7 +700,9 @@ impl cosmic::Application for Window {
             Pages::MainWindow => self.main_window.view(),
             Pages::AppCreator => self.creator_window.view(),
             Pages::IconPicker(picker) => picker.view(),
+            Pages::IconEditor(editor) => editor.view(),
             Pages::IconInstallator(installator) => installator.view(),
+            Pages::Settings(settings) => settings.view(),
         }
     }
 
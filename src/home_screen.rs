@@ -1,6 +1,7 @@
 use crate::{
     common::{get_webapps, icon_cache_get, WebAppLauncher},
     gui::{Buttons, Message},
+    warning::Warning,
 };
 
 use cosmic::{
@@ -15,6 +16,7 @@ use cosmic::{
 pub struct Home {
     pub edit_mode: bool,
     pub launcher: Option<WebAppLauncher>,
+    pub warning: Warning,
 }
 
 impl Home {
@@ -22,6 +24,7 @@ impl Home {
         Home {
             edit_mode: false,
             launcher: None,
+            warning: Warning::default(),
         }
     }
 
@@ -80,6 +83,10 @@ impl Home {
 
         let mut installed = Column::new().spacing(20);
 
+        if self.warning.show {
+            installed = installed.push(self.warning.view());
+        }
+
         if !webapps.is_empty() {
             installed = installed
                 .push(text(format!("You have {} web apps installed:", webapps.len())).size(20));
@@ -0,0 +1,405 @@
+use std::{
+    fs::{remove_file, File},
+    io::{Cursor, Read, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cosmic::widget;
+use image::{io::Reader as ImageReader, DynamicImage, GenericImageView, ImageFormat};
+use reqwest::Client;
+
+use crate::{
+    common::{find_theme_color, is_svg, move_icon, url_valid},
+    iconpicker::{self, IconType},
+};
+
+const CANVAS: f32 = 256.0;
+
+/// Path of the monogram offered in the icon picker, which only exists in
+/// memory until it is chosen.
+pub const MONOGRAM_PATH: &str = "monogram.svg";
+
+pub const SHAPES: [&str; 3] = ["No background", "Rounded square", "Circle"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    None,
+    RoundedSquare,
+    Circle,
+}
+
+impl Shape {
+    pub fn from_index(idx: usize) -> Self {
+        match idx {
+            1 => Shape::RoundedSquare,
+            2 => Shape::Circle,
+            _ => Shape::None,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        match self {
+            Shape::None => 0,
+            Shape::RoundedSquare => 1,
+            Shape::Circle => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+
+        let hex = match hex.len() {
+            3 => hex.chars().flat_map(|c| [c, c]).collect::<String>(),
+            6 => hex.to_string(),
+            _ => return None,
+        };
+
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+
+        Some(Rgb(r, g, b))
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// Mixes the color with white, `amount` being the share of white.
+    pub fn tint(self, amount: f32) -> Self {
+        let mix = |c: u8| (c as f32 + (255.0 - c as f32) * amount).round() as u8;
+
+        Rgb(mix(self.0), mix(self.1), mix(self.2))
+    }
+
+    pub fn is_light(self) -> bool {
+        let luma = 0.299 * self.0 as f32 + 0.587 * self.1 as f32 + 0.114 * self.2 as f32;
+
+        luma > 160.0
+    }
+
+    /// Picks a stable, saturated color for the given text.
+    pub fn from_seed(seed: &str) -> Self {
+        let hash = seed
+            .bytes()
+            .fold(5381u32, |hash, b| hash.wrapping_mul(33) ^ b as u32);
+        let hue = (hash % 360) as f32;
+
+        // HSL to RGB with fixed saturation and lightness
+        let (saturation, lightness) = (0.55, 0.45);
+        let chroma = (1.0 - (2.0 * lightness - 1.0f32).abs()) * saturation;
+        let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+        let m = lightness - chroma / 2.0;
+
+        let (r, g, b) = match hue as u32 / 60 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |c: f32| ((c + m) * 255.0).round() as u8;
+
+        Rgb(channel(r), channel(g), channel(b))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconStyle {
+    pub trim: bool,
+    pub padding: u8,
+    pub shape: Shape,
+    pub background: Option<Rgb>,
+    pub badge: String,
+}
+
+impl Default for IconStyle {
+    fn default() -> Self {
+        Self {
+            trim: true,
+            padding: 12,
+            shape: Shape::RoundedSquare,
+            background: None,
+            badge: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceIcon {
+    pub path: String,
+    data: Vec<u8>,
+    svg: bool,
+}
+
+impl SourceIcon {
+    /// Average color of the opaque pixels, used when no background color is chosen.
+    pub fn dominant_color(&self) -> Option<Rgb> {
+        if self.svg {
+            return None;
+        }
+
+        let image = decode(&self.data)?;
+        let (mut r, mut g, mut b, mut count) = (0u64, 0u64, 0u64, 0u64);
+
+        for (_, _, pixel) in image.pixels() {
+            if pixel[3] > 200 {
+                r += pixel[0] as u64;
+                g += pixel[1] as u64;
+                b += pixel[2] as u64;
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            return None;
+        }
+
+        Some(Rgb((r / count) as u8, (g / count) as u8, (b / count) as u8))
+    }
+}
+
+pub async fn load_source(path: String) -> Option<SourceIcon> {
+    let mut data: Vec<u8> = Vec::new();
+
+    if url_valid(&path) {
+        let response = Client::new().get(&path).send().await.ok()?;
+        data.extend(response.bytes().await.ok()?.to_vec());
+    } else {
+        let pathbuf = PathBuf::from_str(&path).ok()?;
+        File::open(pathbuf).ok()?.read_to_end(&mut data).ok()?;
+    }
+
+    if data.is_empty() {
+        return None;
+    }
+
+    let svg = is_svg(&path);
+
+    Some(SourceIcon { path, data, svg })
+}
+
+fn decode(data: &[u8]) -> Option<DynamicImage> {
+    ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .decode()
+        .ok()
+}
+
+/// Crops fully transparent borders away from the image.
+fn trim_transparent(image: DynamicImage) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (width, height, 0, 0);
+
+    for (x, y, pixel) in image.pixels() {
+        if pixel[3] > 8 {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+
+    if min_x > max_x || min_y > max_y {
+        return image;
+    }
+
+    image.crop_imm(min_x, min_y, max_x - min_x + 1, max_y - min_y + 1)
+}
+
+fn embedded_image(source: &SourceIcon, trim: bool) -> Result<String> {
+    if source.svg {
+        return Ok(format!(
+            "data:image/svg+xml;base64,{}",
+            STANDARD.encode(&source.data)
+        ));
+    }
+
+    let mut image = decode(&source.data).ok_or_else(|| anyhow!("unsupported image format"))?;
+
+    if trim {
+        image = trim_transparent(image);
+    }
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+}
+
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn background_svg(shape: Shape, color: Rgb) -> String {
+    match shape {
+        Shape::None => String::new(),
+        Shape::RoundedSquare => format!(
+            r#"<rect x="0" y="0" width="{0}" height="{0}" rx="{1}" fill="{2}"/>"#,
+            CANVAS,
+            CANVAS * 0.22,
+            color.to_hex()
+        ),
+        Shape::Circle => format!(
+            r#"<circle cx="{0}" cy="{0}" r="{0}" fill="{1}"/>"#,
+            CANVAS / 2.0,
+            color.to_hex()
+        ),
+    }
+}
+
+fn badge_svg(badge: &str) -> String {
+    let badge = badge.trim().to_uppercase();
+
+    if badge.is_empty() {
+        return String::new();
+    }
+
+    let height = CANVAS * 0.26;
+    let width = (badge.chars().count() as f32 * height * 0.62 + height * 0.6).min(CANVAS);
+    let x = (CANVAS - width) / 2.0;
+    let y = CANVAS - height;
+
+    format!(
+        r##"<rect x="{x}" y="{y}" width="{width}" height="{height}" rx="{rx}" fill="#d32f2f" stroke="#ffffff" stroke-width="4"/><text x="{cx}" y="{ty}" font-family="sans-serif" font-weight="bold" font-size="{size}" text-anchor="middle" fill="#ffffff">{text}</text>"##,
+        rx = height * 0.3,
+        cx = CANVAS / 2.0,
+        ty = y + height * 0.73,
+        size = height * 0.62,
+        text = escape_xml(&badge),
+    )
+}
+
+pub fn svg_document(content: &str) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{0}" height="{0}" viewBox="0 0 {0} {0}">{1}</svg>"#,
+        CANVAS, content
+    )
+}
+
+/// Renders the source icon with the given style into a standalone SVG document.
+pub fn compose(source: &SourceIcon, style: &IconStyle) -> Result<String> {
+    let image = embedded_image(source, style.trim)?;
+
+    // icons on a circle have to fit into its inscribed square
+    let inset = match style.shape {
+        Shape::Circle => CANVAS * (1.0 - std::f32::consts::FRAC_1_SQRT_2) / 2.0,
+        _ => 0.0,
+    };
+    let padding = inset + CANVAS * style.padding.min(40) as f32 / 100.0;
+    let size = CANVAS - padding * 2.0;
+
+    let color = style
+        .background
+        .or_else(|| source.dominant_color().map(|c| c.tint(0.8)))
+        .unwrap_or(Rgb(0xee, 0xee, 0xee));
+
+    let mut content = background_svg(style.shape, color);
+    content.push_str(&format!(
+        r#"<image x="{0}" y="{0}" width="{1}" height="{1}" preserveAspectRatio="xMidYMid meet" xlink:href="{2}"/>"#,
+        padding, size, image
+    ));
+    content.push_str(&badge_svg(&style.badge));
+
+    Ok(svg_document(&content))
+}
+
+/// Stores a composed icon in the user icons folder and returns its path.
+pub fn save_composed(svg: &str, output_name: String) -> Result<String> {
+    let temp_path = std::env::temp_dir().join(format!(
+        "io.github.elevenhsoft.WebApps.{}.svg",
+        output_name.replace(' ', "")
+    ));
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(svg.as_bytes())?;
+
+    let temp_path = temp_path.to_str().unwrap().to_string();
+    let saved = move_icon(temp_path.clone(), output_name);
+    let _ = remove_file(temp_path);
+
+    Ok(saved)
+}
+
+fn initials(name: &str) -> String {
+    let initials: String = name
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_' || c == '.')
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(|c| c.to_uppercase())
+        .collect();
+
+    if initials.is_empty() {
+        String::from("?")
+    } else {
+        initials
+    }
+}
+
+/// Builds a monogram from the initials of `name` on a rounded square.
+pub fn monogram_svg(name: &str, color: Rgb) -> String {
+    let initials = initials(name);
+    let size = if initials.chars().count() > 1 {
+        0.42
+    } else {
+        0.55
+    };
+    let foreground = if color.is_light() {
+        "#202020"
+    } else {
+        "#ffffff"
+    };
+
+    let mut content = background_svg(Shape::RoundedSquare, color);
+    content.push_str(&format!(
+        r#"<text x="{0}" y="{1}" font-family="sans-serif" font-weight="bold" font-size="{2}" text-anchor="middle" fill="{3}">{4}</text>"#,
+        CANVAS / 2.0,
+        CANVAS / 2.0 + CANVAS * size * 0.36,
+        CANVAS * size,
+        foreground,
+        escape_xml(&initials),
+    ));
+
+    svg_document(&content)
+}
+
+/// Saves a monogram for the app and returns its path. The color comes from
+/// the site's theme color when known, otherwise it is derived from the URL.
+pub fn create_monogram(name: &str, url: &str, theme_color: Option<Rgb>) -> Result<String> {
+    let color = theme_color.unwrap_or_else(|| Rgb::from_seed(url));
+
+    save_composed(&monogram_svg(name, color), format!("{} monogram", name))
+}
+
+/// Renders the monogram for the icon picker, along with the SVG to save
+/// should it be chosen.
+pub async fn monogram_icon(name: String, url: String) -> (iconpicker::Icon, String) {
+    let theme_color = if url_valid(&url) {
+        find_theme_color(&url)
+            .await
+            .and_then(|color| Rgb::from_hex(&color))
+    } else {
+        None
+    };
+
+    let svg = monogram_svg(&name, theme_color.unwrap_or_else(|| Rgb::from_seed(&url)));
+    let handle = widget::svg::Handle::from_memory(svg.clone().into_bytes());
+
+    (
+        iconpicker::Icon::new(IconType::Svg(handle), MONOGRAM_PATH.to_string()),
+        svg,
+    )
+}
@@ -0,0 +1,171 @@
+use cosmic::{
+    iced::{Alignment, Length},
+    iced_widget::slider,
+    theme,
+    widget::{self, dropdown, text, toggler, Button, Column, Container, Row, TextInput},
+    Command, Element,
+};
+
+use crate::{
+    gui,
+    icon_composer::{compose, IconStyle, Rgb, Shape, SourceIcon, SHAPES},
+};
+
+#[derive(Debug, Clone)]
+pub struct IconEditor {
+    pub source: Option<SourceIcon>,
+    pub style: IconStyle,
+    pub color_input: String,
+    pub preview: Option<widget::svg::Handle>,
+    pub composed: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Trim(bool),
+    Padding(u8),
+    Shape(usize),
+    AutoColor(bool),
+    Color(String),
+    Badge(String),
+}
+
+impl IconEditor {
+    pub fn new() -> Self {
+        IconEditor {
+            source: None,
+            style: IconStyle::default(),
+            color_input: String::new(),
+            preview: None,
+            composed: None,
+        }
+    }
+
+    pub fn set_source(&mut self, source: SourceIcon) {
+        self.source = Some(source);
+        self.render();
+    }
+
+    fn render(&mut self) {
+        self.composed = None;
+        self.preview = None;
+
+        if let Some(source) = &self.source {
+            match compose(source, &self.style) {
+                Ok(svg) => {
+                    let handle = widget::svg::Handle::from_memory(svg.clone().into_bytes());
+                    self.preview = Some(handle);
+                    self.composed = Some(svg);
+                }
+                Err(e) => tracing::error!("Cannot compose icon: {}", e),
+            }
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Trim(trim) => self.style.trim = trim,
+            Message::Padding(padding) => self.style.padding = padding,
+            Message::Shape(idx) => self.style.shape = Shape::from_index(idx),
+            Message::AutoColor(auto) => {
+                self.style.background = if auto {
+                    None
+                } else {
+                    Some(Rgb::from_hex(&self.color_input).unwrap_or(Rgb(0xff, 0xff, 0xff)))
+                };
+            }
+            Message::Color(color) => {
+                if let Some(rgb) = Rgb::from_hex(&color) {
+                    self.style.background = Some(rgb);
+                }
+                self.color_input = color;
+            }
+            Message::Badge(badge) => self.style.badge = badge,
+        }
+
+        self.render();
+
+        Command::none()
+    }
+
+    pub fn view(&self) -> Element<gui::Message> {
+        let preview: Element<gui::Message> = if let Some(handle) = &self.preview {
+            widget::svg(handle.clone())
+                .width(Length::Fixed(160.))
+                .height(Length::Fixed(160.))
+                .into()
+        } else {
+            text("Loading icon...").into()
+        };
+
+        let preview = Container::new(preview)
+            .width(Length::Fill)
+            .center_x()
+            .center_y();
+
+        let trim = toggler(
+            String::from("Trim transparent borders"),
+            self.style.trim,
+            |b| gui::Message::IconEditor(Message::Trim(b)),
+        )
+        .width(Length::Fill);
+
+        let mut padding_row = Row::new().spacing(20).align_items(Alignment::Center);
+        padding_row = padding_row.push(text(format!("Padding {}%", self.style.padding)));
+        padding_row = padding_row.push(slider(0..=40, self.style.padding, |v| {
+            gui::Message::IconEditor(Message::Padding(v))
+        }));
+
+        let shape = dropdown(&SHAPES, Some(self.style.shape.index()), |idx| {
+            gui::Message::IconEditor(Message::Shape(idx))
+        })
+        .width(Length::Fixed(200.));
+
+        let auto_color = toggler(
+            String::from("Automatic color"),
+            self.style.background.is_none(),
+            |b| gui::Message::IconEditor(Message::AutoColor(b)),
+        )
+        .width(Length::Fill);
+
+        let mut color_input =
+            TextInput::new("Background color, e.g. #3584e4", &self.color_input).width(Length::Fill);
+        if self.style.background.is_some() {
+            color_input = color_input.on_input(|s| gui::Message::IconEditor(Message::Color(s)));
+        }
+
+        let mut background_row = Row::new().spacing(20).align_items(Alignment::Center);
+        background_row = background_row.push(shape);
+        background_row = background_row.push(auto_color);
+
+        let badge = TextInput::new("Badge, e.g. DEV or STAGING", &self.style.badge)
+            .on_input(|s| gui::Message::IconEditor(Message::Badge(s)))
+            .width(Length::Fill);
+
+        let mut save = Button::new(Container::new("Use this icon").center_x().center_y())
+            .width(Length::Fill)
+            .style(theme::Button::Suggested);
+        if self.composed.is_some() {
+            save = save.on_press(gui::Message::SaveEditedIcon);
+        }
+        let close = Button::new(Container::new("Cancel").center_x().center_y())
+            .on_press(gui::Message::OpenIconPicker)
+            .width(Length::Fill)
+            .style(theme::Button::Destructive);
+
+        let mut buttons_row = Row::new().spacing(20);
+        buttons_row = buttons_row.push(save);
+        buttons_row = buttons_row.push(close);
+
+        let mut col = Column::new().spacing(20).padding(30);
+        col = col.push(preview);
+        col = col.push(trim);
+        col = col.push(padding_row);
+        col = col.push(background_row);
+        col = col.push(color_input);
+        col = col.push(badge);
+        col = col.push(buttons_row);
+
+        col.into()
+    }
+}
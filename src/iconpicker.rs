@@ -38,9 +38,15 @@ impl IconPicker {
             .padding(8)
             .width(Length::FillPortion(1));
 
+        let edit_icon_btn = widget::button("Edit")
+            .on_press(Message::OpenIconEditor)
+            .padding(8)
+            .width(Length::FillPortion(1));
+
         let mut controls = Row::new().spacing(10);
         controls = controls.push(search_field);
         controls = controls.push(custom_icon_btn);
+        controls = controls.push(edit_icon_btn);
 
         let mut wrapper = crate::wrap::Wrap::new().spacing(8.);
 
@@ -0,0 +1,92 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{browser_detection::host_path, common::home_dir};
+
+/// Where distributions install hunspell dictionaries, which browsers also read.
+const DICTIONARY_DIRS: [&str; 4] = [
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/usr/local/share/hunspell",
+];
+
+/// `de_DE.utf8` or `de_DE` as the `de-DE` tag browsers expect.
+pub fn language_tag(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next()?;
+
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return None;
+    }
+
+    Some(locale.replace('_', "-"))
+}
+
+/// Language tags of the locales the system has generated.
+pub fn installed_locales() -> Vec<String> {
+    // inside flatpak only the runtime's locales are visible, LANG adds the user's
+    let output = Command::new("locale").arg("-a").output();
+
+    let mut locales: BTreeSet<String> = match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(language_tag)
+            .collect(),
+        Err(e) => {
+            tracing::error!("Cannot list locales: {}", e);
+            BTreeSet::new()
+        }
+    };
+
+    if let Some(current) = std::env::var("LANG").ok().as_deref().and_then(language_tag) {
+        locales.insert(current);
+    }
+
+    locales.into_iter().collect()
+}
+
+fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = DICTIONARY_DIRS
+        .iter()
+        .map(|dir| host_path(Path::new(dir)))
+        .collect();
+    dirs.push(home_dir().join(".local/share/hunspell"));
+
+    dirs
+}
+
+/// Language tags of the installed hunspell dictionaries.
+pub fn installed_dictionaries() -> Vec<String> {
+    let mut dictionaries = BTreeSet::new();
+
+    for dir in dictionary_dirs() {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            // hyphenation and thesaurus files, e.g. hyph_de_DE.dic, come without one
+            if path.extension().and_then(|ext| ext.to_str()) != Some("dic")
+                || !path.with_extension("aff").exists()
+            {
+                continue;
+            }
+
+            if let Some(tag) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(language_tag)
+            {
+                dictionaries.insert(tag);
+            }
+        }
+    }
+
+    dictionaries.into_iter().collect()
+}
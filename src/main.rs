@@ -1,10 +1,17 @@
+mod browser_detection;
 mod common;
 mod creator;
+mod engines;
 mod gui;
 mod home_screen;
 mod icon_cache;
+mod icon_composer;
+mod icon_editor;
 mod iconpicker;
 mod icons_installator;
+mod languages;
+mod profiles;
+mod settings;
 mod supported_browsers;
 mod warning;
 mod wrap;
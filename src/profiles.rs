@@ -0,0 +1,214 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use walkdir::WalkDir;
+
+use crate::{
+    common::{browser_id, home_dir, Browser},
+    engines::{quote_arg, Retention},
+};
+
+/// How a browser is installed, which decides where it may keep its profiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallSource {
+    Native,
+    Flatpak(String),
+    Snap(String),
+}
+
+impl InstallSource {
+    pub fn of(browser: &Browser) -> Self {
+        // user defined browsers may use any id, their location tells the truth
+        let id = if browser.id.starts_with("flatpak") || browser.id.starts_with("snap:") {
+            browser.id.clone()
+        } else {
+            browser_id(browser.test_path())
+        };
+
+        match id.split_once(':') {
+            Some((source, app)) if source.starts_with("flatpak") => {
+                InstallSource::Flatpak(app.to_string())
+            }
+            Some(("snap", name)) => InstallSource::Snap(name.to_string()),
+            _ => InstallSource::Native,
+        }
+    }
+
+    /// Directory the browser can always write to, even when confined.
+    pub fn data_dir(&self) -> PathBuf {
+        match self {
+            InstallSource::Native => home_dir().join(".local/share"),
+            InstallSource::Flatpak(app_id) => home_dir().join(".var/app").join(app_id).join("data"),
+            InstallSource::Snap(name) => home_dir().join("snap").join(name).join("common"),
+        }
+    }
+}
+
+/// Root of the isolated profiles of one engine, e.g. `~/.local/share/ice/firefox`
+/// or `~/.var/app/org.mozilla.firefox/data/ice/firefox`.
+pub fn profile_root(browser: &Browser, engine_dir: &str) -> PathBuf {
+    InstallSource::of(browser)
+        .data_dir()
+        .join("ice")
+        .join(engine_dir)
+}
+
+/// Whether a flatpak browser needs an override to reach `path`.
+pub fn needs_override(browser: &Browser, path: &Path) -> Option<String> {
+    match InstallSource::of(browser) {
+        InstallSource::Flatpak(app_id) => {
+            let app_dir = home_dir().join(".var/app").join(&app_id);
+
+            if path.starts_with(app_dir) {
+                None
+            } else {
+                Some(app_id)
+            }
+        }
+        _ => None,
+    }
+}
+
+pub fn override_command(app_id: &str, path: &Path) -> String {
+    format!(
+        "flatpak override --user --filesystem={} {}",
+        path.display(),
+        app_id
+    )
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    for entry in WalkDir::new(from) {
+        let entry = entry?;
+        let target = to.join(entry.path().strip_prefix(from)?);
+
+        if entry.file_type().is_dir() {
+            create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves a profile created by older versions in a location the browser
+/// cannot use to `target`. An existing profile at `target` always wins.
+pub fn migrate_profile(legacy: &Path, target: &Path) -> Result<()> {
+    if legacy == target || !legacy.is_dir() || target.exists() {
+        return Ok(());
+    }
+
+    if let Some(parent) = target.parent() {
+        create_dir_all(parent)?;
+    }
+
+    // rename fails across file systems, e.g. into a snap's home
+    if fs::rename(legacy, target).is_err() {
+        copy_dir(legacy, target)?;
+        fs::remove_dir_all(legacy)?;
+    }
+
+    tracing::info!("Moved profile {:?} to {:?}.", legacy, target);
+
+    Ok(())
+}
+
+/// Sibling of `profile_path` with `suffix` appended to its name.
+fn profile_sibling(profile_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = profile_path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    profile_path.with_file_name(name)
+}
+
+/// Pristine copy of an ephemeral profile, taken at its first launch.
+fn seed_path(profile_path: &Path) -> PathBuf {
+    profile_sibling(profile_path, ".seed")
+}
+
+/// Puts the pristine copy of an ephemeral profile back in place, so changes
+/// apply to it and not to the data of the last launch.
+pub fn restore_seed(profile_path: &Path) -> Result<()> {
+    let seed = seed_path(profile_path);
+
+    if !seed.is_dir() {
+        return Ok(());
+    }
+
+    if profile_path.exists() {
+        fs::remove_dir_all(profile_path)?;
+    }
+
+    fs::rename(seed, profile_path)?;
+
+    Ok(())
+}
+
+/// Removes what the launch script keeps next to the profile.
+pub fn remove_launch_files(profile_path: &Path) {
+    let _ = fs::remove_dir_all(seed_path(profile_path));
+    let _ = fs::remove_file(profile_sibling(profile_path, ".lock"));
+}
+
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', "'\\''"))
+}
+
+/// Wraps `exec` in a shell which resets the profile before the browser starts.
+///
+/// The lock is held by the browser as long as it runs, a second launch must
+/// not pull the profile away from under it.
+pub fn launch_script(
+    exec: &str,
+    profile_path: &Path,
+    retention: Retention,
+    cache_dirs: &[PathBuf],
+) -> String {
+    let setup = match retention {
+        Retention::Ephemeral => {
+            let profile = shell_quote(profile_path);
+            let seed = shell_quote(&seed_path(profile_path));
+
+            format!(
+                "[ -d {seed} ] || cp -a {profile} {seed}; rm -rf {profile}; cp -a {seed} {profile}"
+            )
+        }
+        retention if retention.clears_cache() && !cache_dirs.is_empty() => {
+            let dirs: Vec<String> = cache_dirs.iter().map(|dir| shell_quote(dir)).collect();
+            format!("rm -rf {}", dirs.join(" "))
+        }
+        _ => return exec.to_string(),
+    };
+
+    let script = format!(
+        "exec 9>>{}; if flock -n 9; then {}; fi; exec \"$@\"",
+        shell_quote(&profile_sibling(profile_path, ".lock")),
+        setup
+    );
+
+    format!("sh -c {} sh {}", quote_arg(&script), exec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::BrowserType;
+
+    #[test]
+    fn flatpak_profiles_need_no_override() {
+        let mut browser = Browser::new(BrowserType::Firefox, "Firefox", "", "");
+        browser.set_id(String::from("flatpak-user:org.mozilla.firefox"));
+
+        let profile = profile_root(&browser, "firefox").join("webapp");
+        assert_eq!(needs_override(&browser, &profile), None);
+
+        let downloads = home_dir().join("Downloads");
+        assert_eq!(
+            needs_override(&browser, &downloads),
+            Some(String::from("org.mozilla.firefox"))
+        );
+    }
+}
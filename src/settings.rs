@@ -0,0 +1,215 @@
+use cosmic::{
+    iced::{Alignment, Length},
+    iced_widget::Scrollable,
+    style, theme,
+    widget::{self, dropdown, text, Button, Column, Container, Row, TextInput},
+    Command, Element,
+};
+
+use crate::{
+    common::{icon_cache_get, ENGINES},
+    engines::firefox_prefs::Prefs,
+    gui,
+    supported_browsers::{save_user_definitions, user_definitions, BrowserDefinition},
+};
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub definitions: Vec<BrowserDefinition>,
+    pub firefox_prefs: Vec<(String, String)>,
+    pub status: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Id(usize, String),
+    Name(usize, String),
+    Engine(usize, usize),
+    Exec(usize, String),
+    Test(usize, String),
+    Command(usize, String),
+    Add,
+    Remove(usize),
+    PrefKey(usize, String),
+    PrefValue(usize, String),
+    AddPref,
+    RemovePref(usize),
+    Save,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Settings {
+            definitions: user_definitions(),
+            firefox_prefs: Prefs::global().to_pairs(),
+            status: String::new(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Command<Message> {
+        self.status.clear();
+
+        match message {
+            Message::Id(idx, id) => self.definitions[idx].id = id,
+            Message::Name(idx, name) => self.definitions[idx].name = name,
+            Message::Engine(idx, engine) => {
+                self.definitions[idx].engine = ENGINES[engine].to_string()
+            }
+            Message::Exec(idx, exec) => self.definitions[idx].exec = exec,
+            Message::Test(idx, test) => self.definitions[idx].test = test,
+            Message::Command(idx, command) => self.definitions[idx].command = command,
+            Message::Add => self.definitions.push(BrowserDefinition {
+                id: format!("custom:browser{}", self.definitions.len() + 1),
+                engine: ENGINES[0].to_string(),
+                ..Default::default()
+            }),
+            Message::Remove(idx) => {
+                self.definitions.remove(idx);
+            }
+            Message::PrefKey(idx, key) => self.firefox_prefs[idx].0 = key,
+            Message::PrefValue(idx, value) => self.firefox_prefs[idx].1 = value,
+            Message::AddPref => self.firefox_prefs.push((String::new(), String::new())),
+            Message::RemovePref(idx) => {
+                self.firefox_prefs.remove(idx);
+            }
+            Message::Save => {
+                self.definitions.retain(|d| !d.id.is_empty());
+
+                let saved = save_user_definitions(&self.definitions)
+                    .and_then(|_| Prefs::from_pairs(&self.firefox_prefs).save_global());
+
+                match saved {
+                    Ok(_) => self.status = String::from("Settings saved."),
+                    Err(e) => self.status = format!("Cannot save settings: {}", e),
+                }
+            }
+        }
+
+        Command::none()
+    }
+
+    fn definition_view(&self, idx: usize) -> Element<gui::Message> {
+        let definition = &self.definitions[idx];
+
+        let id = TextInput::new("Id, e.g. native:my-browser", &definition.id)
+            .on_input(move |s| gui::Message::Settings(Message::Id(idx, s)))
+            .width(Length::Fill);
+        let name = TextInput::new("Name", &definition.name)
+            .on_input(move |s| gui::Message::Settings(Message::Name(idx, s)))
+            .width(Length::Fill);
+        let engine = dropdown(
+            &ENGINES,
+            ENGINES.iter().position(|e| *e == definition.engine),
+            move |engine| gui::Message::Settings(Message::Engine(idx, engine)),
+        )
+        .width(Length::Fixed(200.));
+        let exec = TextInput::new("Executable", &definition.exec)
+            .on_input(move |s| gui::Message::Settings(Message::Exec(idx, s)))
+            .width(Length::Fill);
+        let test = TextInput::new("Detection path", &definition.test)
+            .on_input(move |s| gui::Message::Settings(Message::Test(idx, s)))
+            .width(Length::Fill);
+        let command = TextInput::new(
+            "Command template, e.g. {exec} --app={url} --class={class} {private} {args}",
+            &definition.command,
+        )
+        .on_input(move |s| gui::Message::Settings(Message::Command(idx, s)))
+        .width(Length::Fill);
+
+        let remove = widget::button(icon_cache_get("edit-delete-symbolic", 16))
+            .on_press(gui::Message::Settings(Message::Remove(idx)))
+            .padding(8)
+            .style(style::Button::Icon);
+
+        let mut header = Row::new().spacing(10).align_items(Alignment::Center);
+        header = header.push(id);
+        header = header.push(engine);
+        header = header.push(remove);
+
+        let mut col = Column::new().spacing(10);
+        col = col.push(header);
+        col = col.push(name);
+        col = col.push(exec);
+        col = col.push(test);
+        col = col.push(command);
+
+        col.into()
+    }
+
+    fn prefs_view(&self) -> Element<gui::Message> {
+        let add = widget::button(icon_cache_get("list-add-symbolic", 16))
+            .on_press(gui::Message::Settings(Message::AddPref))
+            .padding(8)
+            .style(style::Button::Icon);
+
+        let mut header = Row::new().spacing(10).align_items(Alignment::Center);
+        header = header.push(text("Firefox preferences of all web apps").width(Length::Fill));
+        header = header.push(add);
+
+        let mut col = Column::new().spacing(10);
+        col = col.push(header);
+
+        for (idx, (key, value)) in self.firefox_prefs.iter().enumerate() {
+            let key = TextInput::new("Preference", key)
+                .on_input(move |s| gui::Message::Settings(Message::PrefKey(idx, s)))
+                .width(Length::Fill);
+            let value = TextInput::new("Value", value)
+                .on_input(move |s| gui::Message::Settings(Message::PrefValue(idx, s)))
+                .width(Length::Fill);
+            let remove = widget::button(icon_cache_get("edit-delete-symbolic", 16))
+                .on_press(gui::Message::Settings(Message::RemovePref(idx)))
+                .padding(8)
+                .style(style::Button::Icon);
+
+            let mut row = Row::new().spacing(10).align_items(Alignment::Center);
+            row = row.push(key);
+            row = row.push(value);
+            row = row.push(remove);
+
+            col = col.push(row);
+        }
+
+        col.into()
+    }
+
+    pub fn view(&self) -> Element<gui::Message> {
+        let info = text(
+            "Browser definitions extend the bundled list. A definition with the id of a \
+             bundled one replaces it. Placeholders: {exec} {url} {class} {profile} {private} {args}",
+        );
+
+        let mut list = Column::new().spacing(30);
+
+        for idx in 0..self.definitions.len() {
+            list = list.push(self.definition_view(idx));
+        }
+
+        list = list.push(self.prefs_view());
+
+        let scrollable = Scrollable::new(list)
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        let add = Button::new(Container::new("Add browser").center_x().center_y())
+            .on_press(gui::Message::Settings(Message::Add))
+            .width(Length::Fill);
+        let save = Button::new(Container::new("Save").center_x().center_y())
+            .on_press(gui::Message::Settings(Message::Save))
+            .width(Length::Fill)
+            .style(theme::Button::Suggested);
+
+        let mut buttons_row = Row::new().spacing(20);
+        buttons_row = buttons_row.push(add);
+        buttons_row = buttons_row.push(save);
+
+        let mut col = Column::new().spacing(20).padding(30);
+        col = col.push(info);
+        col = col.push(scrollable);
+        if !self.status.is_empty() {
+            col = col.push(text(self.status.clone()));
+        }
+        col = col.push(buttons_row);
+
+        col.into()
+    }
+}
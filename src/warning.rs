@@ -12,6 +12,8 @@ pub enum WarnMessages {
     AppUrl,
     AppIcon,
     AppBrowser,
+    TitleColor,
+    Extensions(String),
 }
 
 impl std::fmt::Display for WarnMessages {
@@ -27,6 +29,11 @@ impl std::fmt::Display for WarnMessages {
             WarnMessages::AppBrowser => {
                 write!(f, "  - Please select a browser. Make sure at least one is installed system-wide or via flatpak.")
             }
+            WarnMessages::TitleColor => write!(
+                f,
+                "  - Title bar color must be a hex color like #3584e4 or a color name"
+            ),
+            WarnMessages::Extensions(e) => write!(f, "Cannot install Firefox extensions: {}", e),
         }
     }
 }